@@ -0,0 +1,248 @@
+use anchor_lang::prelude::*;
+use super::{AcceptAdmin, CancelChange, CheckSolvency, Change, Config, Economy, ExecuteChange, InitializeConfig, InitializeParams, ProposeAdmin, ProposeChange, SetPause, SetRoles};
+use crate::constants::*;
+use crate::events::{AdminChanged, AdminProposed, ChangeCancelled, ChangeProposed, ConfigInitialized, EmissionUpdated, MultiplierUpdated, ParamsUpdated, PauseUpdated, PoolUpdated, RolesUpdated, SolvencyChecked};
+use crate::errors;
+use crate::game_state::{self, AvgStats, NftQualities};
+use crate::pool_state::Pool;
+
+pub fn initialize_config(ctx: Context<InitializeConfig>, max_supply: u64, daily_mint_cap: u64, emission_per_day: u64) -> Result<()> {
+    if emission_per_day > daily_mint_cap { //same bound as propose_emission
+        return Err(errors::ErrorCode::InvalidParams.into())
+    }
+
+    let (_vault_authority, vault_authority_bump) = Pubkey::find_program_address(&[VAULT_PDA_SEED], ctx.program_id);
+
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.storage.key();
+    config.mint = ctx.accounts.mint.key();
+    config.vault = ctx.accounts.vault_token.key();
+    config.vault_bump = vault_authority_bump;
+    config.bump = *ctx.bumps.get("config").unwrap();
+    config.max_supply = max_supply;
+    config.daily_mint_cap = daily_mint_cap;
+    config.total_minted = 0;
+    config.minted_today = 0;
+    config.mint_day = 0;
+    config.pending_admin = None;
+    config.oracle = config.admin; //every role starts with the admin until set_roles
+    config.treasurer = config.admin;
+    config.param_manager = config.admin;
+    config.multisig_enabled = false;
+    config.large_mint = 0;
+    config.paused = 0;
+    config.total_claimable = 0;
+    config.emission_per_day = emission_per_day; //later rates go through propose_emission
+    config.emission_day = 0; //set by initialize_params once the days can be counted
+    config.emission_accrued = 0;
+    config.emitted = 0;
+    config.sold_today = 0;
+    config.sell_day = 0;
+
+    emit!(ConfigInitialized {
+        admin: config.admin,
+        mint: config.mint,
+        vault: config.vault,
+        max_supply,
+        daily_mint_cap,
+        emission_per_day,
+    });
+
+    Ok(())
+}
+
+//Fn to split the privileged instructions between keys, so a compromised game server cannot reach the treasury
+pub fn set_roles(ctx: Context<SetRoles>, oracle: Pubkey, treasurer: Pubkey, param_manager: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.oracle = oracle;
+    config.treasurer = treasurer;
+    config.param_manager = param_manager;
+
+    emit!(RolesUpdated {
+        oracle,
+        treasurer,
+        param_manager,
+    });
+
+    Ok(())
+}
+
+//Fn to stop or resume instruction groups in an emergency, replaces all the flags at once
+pub fn set_pause(ctx: Context<SetPause>, paused: u8) -> Result<()> {
+    if paused & !PAUSE_ALL != 0 {
+        return Err(errors::ErrorCode::InvalidPauseFlags.into())
+    }
+    ctx.accounts.config.paused = paused;
+
+    emit!(PauseUpdated { paused });
+
+    Ok(())
+}
+
+//Fn to compare the unclaimed rewards with the vault, read the event by simulating the transaction
+pub fn check_solvency(ctx: Context<CheckSolvency>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let vault_balance = ctx.accounts.vault_token.amount;
+
+    emit!(SolvencyChecked {
+        total_claimable: config.total_claimable,
+        vault_balance,
+        reserves: config.reserves(vault_balance, ctx.accounts.mint.supply, ctx.accounts.params.economy.current_day()?),
+        solvent: config.total_claimable <= vault_balance,
+    });
+
+    Ok(())
+}
+
+//Fn to start rotating the admin key, the mint and vault authorities stay with the vault PDA
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_admin = Some(new_admin); //replaces an earlier proposal
+
+    emit!(AdminProposed {
+        admin: config.admin,
+        pending_admin: new_admin,
+    });
+
+    Ok(())
+}
+
+//Fn for the proposed admin to take over, the new key has to sign, roles still held by the old key move with it
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let admin = ctx.accounts.new_admin.key();
+    let config: &mut Config = &mut ctx.accounts.config;
+    let previous = config.admin;
+    config.admin = admin;
+    config.pending_admin = None;
+
+    let mut roles_moved = false;
+    for role in [&mut config.oracle, &mut config.treasurer, &mut config.param_manager] {
+        if *role == previous {
+            *role = admin;
+            roles_moved = true;
+        }
+    }
+
+    emit!(AdminChanged {
+        previous,
+        admin: config.admin,
+    });
+    if roles_moved {
+        emit!(RolesUpdated {
+            oracle: config.oracle,
+            treasurer: config.treasurer,
+            param_manager: config.param_manager,
+        });
+    }
+
+    Ok(())
+}
+
+pub fn initialize_params(ctx: Context<InitializeParams>, economy: Economy) -> Result<()> {
+    economy.validate()?;
+
+    let params = &mut ctx.accounts.params;
+    params.economy = economy;
+    params.bump = *ctx.bumps.get("params").unwrap();
+
+    ctx.accounts.config.emission_day = economy.current_day()?; //the initial emission rate is released from the first day of the economy
+
+    emit!(ParamsUpdated { economy });
+
+    Ok(())
+}
+
+pub fn propose_params(ctx: Context<ProposeChange>, economy: Economy) -> Result<()> {
+    economy.validate()?;
+    economy.ensure_same_days(&ctx.accounts.params.economy)?;
+    propose(ctx, Change::Params { economy })
+}
+
+pub fn propose_nft_multiplier(ctx: Context<ProposeChange>, stats: AvgStats, nfts: NftQualities) -> Result<()> {
+    let multipliers = game_state::nft_multipliers(&stats, &nfts)?; //computed now so the pending values are public
+    if !multipliers.iter().all(|multiplier| (MIN_NFT_MULTIPLIER..=MAX_NFT_MULTIPLIER).contains(multiplier)) {
+        return Err(errors::ErrorCode::InvalidParams.into())
+    }
+    let [common, rare, epic, legendary] = multipliers;
+    propose(ctx, Change::NftMultiplier { common, rare, epic, legendary })
+}
+
+pub fn propose_emission(ctx: Context<ProposeChange>, per_day: u64) -> Result<()> {
+    if per_day > ctx.accounts.config.daily_mint_cap { //the schedule never outpaces the treasury budget
+        return Err(errors::ErrorCode::InvalidParams.into())
+    }
+    propose(ctx, Change::Emission { per_day })
+}
+
+pub fn propose_pool(ctx: Context<ProposeChange>, fee: u64, vault_share: u64) -> Result<()> {
+    Pool::validate(fee, vault_share)?;
+    propose(ctx, Change::Pool { fee, vault_share })
+}
+
+//Store the change with the earliest time it can be executed at
+fn propose(ctx: Context<ProposeChange>, change: Change) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.proposer = ctx.accounts.storage.key();
+    proposal.change = change;
+    proposal.effective_at = Clock::get()?.unix_timestamp + TIMELOCK_DELAY;
+    proposal.bump = *ctx.bumps.get("proposal").unwrap();
+
+    emit!(ChangeProposed {
+        change,
+        effective_at: proposal.effective_at,
+    });
+
+    Ok(())
+}
+
+pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+    emit!(ChangeCancelled {
+        change: ctx.accounts.proposal.change,
+    });
+
+    Ok(())
+}
+
+//Fn to apply the pending change after the timelock, anyone can call it
+pub fn execute_change<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteChange<'info>>) -> Result<()> {
+    let proposal = &ctx.accounts.proposal;
+    if Clock::get()?.unix_timestamp < proposal.effective_at {
+        return Err(errors::ErrorCode::TimelockNotExpired.into())
+    }
+
+    match proposal.change {
+        Change::Params { economy } => {
+            ctx.accounts.params.economy = economy;
+            emit!(ParamsUpdated { economy });
+        },
+        Change::NftMultiplier { common, rare, epic, legendary } => {
+            let nft_multiplier = &mut ctx.accounts.nft_multiplier;
+            nft_multiplier.common = common;
+            nft_multiplier.rare = rare;
+            nft_multiplier.epic = epic;
+            nft_multiplier.legendary = legendary;
+            emit!(MultiplierUpdated { common, rare, epic, legendary });
+        },
+        Change::Emission { per_day } => {
+            let day = ctx.accounts.params.economy.current_day()?;
+            ctx.accounts.config.set_emission(per_day, day); //the new rate only releases tokens from today on
+            emit!(EmissionUpdated { per_day, day });
+        },
+        Change::Pool { fee, vault_share } => {
+            let info = match ctx.remaining_accounts.first() {
+                Some(info) if info.is_writable => info,
+                _ => return Err(errors::ErrorCode::InvalidPool.into()),
+            };
+            let mut pool = Account::<Pool>::try_from(info)?; //checks the owner and the discriminator
+            if Pubkey::create_program_address(&[POOL_PDA_SEED, &[pool.bump]], ctx.program_id) != Ok(info.key()) {
+                return Err(errors::ErrorCode::InvalidPool.into())
+            }
+            pool.fee = fee;
+            pool.vault_share = vault_share;
+            pool.exit(ctx.program_id)?;
+            emit!(PoolUpdated { fee, vault_share });
+        },
+    };
+
+    Ok(())
+}
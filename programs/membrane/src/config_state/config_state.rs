@@ -0,0 +1,332 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{Mint, TokenAccount};
+use crate::constants::*;
+use crate::errors;
+use crate::maths::QualityMultiplier;
+
+#[derive(Accounts)]
+#[instruction(max_supply: u64)]
+pub struct InitializeConfig<'info> {
+    #[account(init, payer = storage, space = MAX_CONFIG_SIZE, seeds = [CONFIG_PDA_SEED], bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(constraint = mint.mint_authority == COption::Some(storage.key()) @ errors::ErrorCode::Unauthorized,
+        constraint = mint.supply <= max_supply @ errors::ErrorCode::SupplyCapExceeded)]
+    pub mint: Account<'info, Mint>,
+    #[account(constraint = vault_token.mint == mint.key() @ errors::ErrorCode::InvalidMint)]
+    pub vault_token: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == storage.key() @ errors::ErrorCode::Unauthorized,
+        constraint = !config.multisig_enabled @ errors::ErrorCode::MultisigRequired)]
+    pub config: Account<'info, Config>,
+    pub storage: Signer<'info>,
+    /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
+    pub authority: AccountInfo<'info>, //PDA
+    #[account(address = config.mint @ errors::ErrorCode::InvalidMint,
+        constraint = mint.mint_authority == COption::Some(authority.key()) @ errors::ErrorCode::AuthorityNotTransferred,
+        constraint = mint.freeze_authority == COption::Some(authority.key()) @ errors::ErrorCode::AuthorityNotTransferred)]
+    pub mint: Account<'info, Mint>,
+    #[account(address = config.vault @ errors::ErrorCode::InvalidVault,
+        constraint = vault_token.owner == authority.key() @ errors::ErrorCode::AuthorityNotTransferred)]
+    pub vault_token: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoles<'info> {
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == storage.key() @ errors::ErrorCode::Unauthorized,
+        constraint = !config.multisig_enabled @ errors::ErrorCode::MultisigRequired)]
+    pub config: Account<'info, Config>,
+    pub storage: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == storage.key() @ errors::ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub storage: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CheckSolvency<'info> {
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
+    #[account(address = config.vault @ errors::ErrorCode::InvalidVault)]
+    pub vault_token: Account<'info, TokenAccount>,
+    #[account(address = config.mint @ errors::ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.pending_admin == Some(new_admin.key()) @ errors::ErrorCode::NotPendingAdmin)]
+    pub config: Account<'info, Config>,
+    pub new_admin: Signer<'info>,
+}
+
+#[account]
+pub struct Config { //singleton, every privileged instruction is checked against it
+    pub admin: Pubkey, //storage key
+    pub mint: Pubkey, //PLASMA mint
+    pub vault: Pubkey, //vault token account
+    pub vault_bump: u8, //bump of the VAULT_PDA_SEED authority
+    pub bump: u8,
+    pub max_supply: u64, //hard cap on the mint supply
+    pub daily_mint_cap: u64, //emission budget per day
+    pub total_minted: u64, //cumulative amount minted by the program
+    pub minted_today: u64, //amount minted during mint_day
+    pub mint_day: i64, //days since Economy.start of the last mint
+    pub pending_admin: Option<Pubkey>, //proposed admin until it accepts
+    pub oracle: Pubkey, //game server, starts, ends and settles games
+    pub treasurer: Pubkey, //mints, freezes, withdraws the treasury and returns the authorities
+    pub param_manager: Pubkey, //proposes economy and nft multiplier changes
+    pub multisig_enabled: bool, //return_authority, large mints, treasury withdrawals, propose_admin and set_roles need the multisig
+    pub large_mint: u64, //largest daily total mint_token accepts without the multisig
+    pub paused: u8, //PAUSE_* flags of the instruction groups that are stopped
+    pub total_claimable: u64, //rewards credited to players and not claimed yet
+    pub emission_per_day: u64, //tokens the schedule releases for vault top ups every day
+    pub emission_day: i64, //days since Economy.start the current rate applies from
+    pub emission_accrued: u64, //tokens released by the earlier rates before emission_day
+    pub emitted: u64, //cumulative amount minted into the vault by the schedule
+    pub sold_today: u64, //amount bought back during sell_day
+    pub sell_day: i64, //days since Economy.start of the last buyback
+}
+
+impl Config {
+    pub fn ensure_unpaused(&self, group: u8) -> Result<()> {
+        if self.paused & group != 0 {
+            return Err(errors::ErrorCode::Paused.into())
+        }
+        Ok(())
+    }
+
+    //Tokens that can still back the claims, the vault balance and what the emission schedule can still top it up with
+    pub fn reserves(&self, vault_balance: u64, supply: u64, day: i64) -> u64 {
+        vault_balance.saturating_add(self.emission_allowance(day, supply))
+    }
+
+    //Add a credit to the liabilities, refused once the reserves could not cover them
+    pub fn record_credit(&mut self, amount: u64, vault_balance: u64, supply: u64, day: i64) -> Result<()> {
+        self.total_claimable = match self.total_claimable.checked_add(amount) {
+            Some(total) if total <= self.reserves(vault_balance, supply, day) => total,
+            _ => return Err(errors::ErrorCode::InsufficientReserves.into()),
+        };
+        Ok(())
+    }
+
+    //Tokens released by the schedule up to and including the day
+    fn scheduled_emission(&self, day: i64) -> u64 {
+        let days = (day - self.emission_day + 1).max(0) as u64;
+        self.emission_accrued.saturating_add(self.emission_per_day.saturating_mul(days))
+    }
+
+    //Switch the schedule to a new rate from the day on, what the earlier rates released stays available
+    pub fn set_emission(&mut self, per_day: u64, day: i64) {
+        self.emission_accrued = self.scheduled_emission(day - 1);
+        self.emission_day = day;
+        self.emission_per_day = per_day;
+    }
+
+    //Tokens the emission schedule still allows to mint into the vault
+    pub fn emission_allowance(&self, day: i64, supply: u64) -> u64 {
+        self.scheduled_emission(day)
+            .saturating_sub(self.emitted)
+            .min(self.max_supply.saturating_sub(supply))
+    }
+
+    //Record a vault top up, it shares the daily budget with mint_token
+    pub fn record_emission(&mut self, amount: u64, day: i64) {
+        if day != self.mint_day {
+            self.mint_day = day;
+            self.minted_today = 0;
+        }
+        self.minted_today += amount;
+        self.emitted += amount;
+        self.total_minted += amount;
+    }
+
+    pub fn record_claim(&mut self, amount: u64) {
+        self.total_claimable = self.total_claimable.saturating_sub(amount); //credits made before the counter existed are not in it
+    }
+
+    //Check the daily buyback limit, then record the sold amount
+    pub fn record_sell(&mut self, amount: u64, day: i64, limit: u64) -> Result<()> {
+        if day != self.sell_day { //limit resets every day
+            self.sell_day = day;
+            self.sold_today = 0;
+        }
+        self.sold_today = match self.sold_today.checked_add(amount) {
+            Some(sold) if sold <= limit => sold,
+            _ => return Err(errors::ErrorCode::DailySellLimitExceeded.into()),
+        };
+        Ok(())
+    }
+
+    //Amount minted during the day, the counter only holds the amount of mint_day
+    pub fn minted_on(&self, day: i64) -> u64 {
+        if day == self.mint_day { self.minted_today } else { 0 }
+    }
+
+    //Part of the daily budget left for the day
+    pub fn mint_budget(&self, day: i64) -> u64 {
+        self.daily_mint_cap.saturating_sub(self.minted_on(day))
+    }
+
+    //Check the supply cap and the daily budget, then record the minted amount
+    pub fn record_mint(&mut self, supply: u64, amount: u64, day: i64) -> Result<()> {
+        if day != self.mint_day { //budget resets every day
+            self.mint_day = day;
+            self.minted_today = 0;
+        }
+
+        match supply.checked_add(amount) {
+            Some(new_supply) if new_supply <= self.max_supply => (),
+            _ => return Err(errors::ErrorCode::SupplyCapExceeded.into()),
+        };
+        self.minted_today = match self.minted_today.checked_add(amount) {
+            Some(minted) if minted <= self.daily_mint_cap => minted,
+            _ => return Err(errors::ErrorCode::DailyMintCapExceeded.into()),
+        };
+        self.total_minted += amount;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeParams<'info> {
+    #[account(init, payer = storage, space = MAX_PARAMS_SIZE, seeds = [PARAMS_PDA_SEED], bump)]
+    pub params: Account<'info, Params>,
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == storage.key() @ errors::ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeChange<'info> {
+    #[account(init, payer = storage, space = MAX_PROPOSAL_SIZE, seeds = [PROPOSAL_PDA_SEED], bump)]
+    pub proposal: Account<'info, Proposal>, //one pending change at a time
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.param_manager == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    #[account(mut, close = storage, seeds = [PROPOSAL_PDA_SEED], bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump,
+        constraint = config.param_manager == storage.key() || config.admin == storage.key() @ errors::ErrorCode::MissingRole)] //the admin can veto a change of the parameter manager
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> { //a pool change passes the pool through remaining_accounts, it may not exist yet
+    #[account(mut, close = proposer, seeds = [PROPOSAL_PDA_SEED], bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: SAFE, receives the rent of the proposal
+    #[account(mut, address = proposal.proposer @ errors::ErrorCode::Unauthorized)]
+    pub proposer: AccountInfo<'info>,
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
+    #[account(mut, seeds = [MULTIPLIER_PDA_SEED], bump = nft_multiplier.bump)]
+    pub nft_multiplier: Account<'info, QualityMultiplier>,
+}
+
+#[account]
+pub struct Params { //singleton, economic parameters that can change without a redeploy
+    pub economy: Economy,
+    pub bump: u8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct Economy {
+    pub start: i64, //unix timestamp of day 0 of the reward curve
+    pub sec_in_day: i64, //seconds in day to calculate current day from the start
+    pub nft_price: u64,
+    pub victory: u64, //share of the reward for the placement in REWARD_SHARE_BASE parts
+    pub top_five: u64, //2-5
+    pub top_ten: u64, //6-10
+    pub kill: u64, //per kill
+    pub fee_lamports: u64, //paid to storage when a player registers
+    pub sell_price: u64, //lamports the treasury pays per PLASMA in user_sell
+    pub sell_burn: u64, //part of the sold PLASMA that is burned in BPS, the rest stays in the vault
+    pub treasury_share: u64, //part of the price the treasury keeps in BPS
+    pub daily_sell_limit: u64, //PLASMA bought back per day across all players
+}
+
+impl Economy {
+    pub fn validate(&self) -> Result<()> {
+        let valid = self.start > 0
+            && (MIN_SEC_IN_DAY..=MAX_SEC_IN_DAY).contains(&self.sec_in_day)
+            && (1..=MAX_NFT_PRICE).contains(&self.nft_price)
+            && (1..=REWARD_SHARE_BASE).contains(&self.victory)
+            && self.top_five <= self.victory //a better placement never pays less
+            && self.top_ten <= self.top_five
+            && self.kill <= self.victory
+            && self.fee_lamports <= MAX_FEE_LAMPORTS
+            && self.sell_price <= MAX_SELL_PRICE
+            && self.sell_burn <= BPS
+            && self.treasury_share <= BPS;
+        if !valid {
+            return Err(errors::ErrorCode::InvalidParams.into())
+        }
+        Ok(())
+    }
+
+    //Every day-indexed counter (emission schedule, mint and sell days, reward tables) is counted from start in sec_in_day,
+    //moving either would rescale the days already counted, e.g. a shorter day multiplies the emission allowance
+    pub fn ensure_same_days(&self, current: &Economy) -> Result<()> {
+        if self.start != current.start || self.sec_in_day != current.sec_in_day {
+            return Err(errors::ErrorCode::ImmutableDays.into())
+        }
+        Ok(())
+    }
+
+    //Lamports paid for the amount of PLASMA after the treasury share
+    pub fn sell_payout(&self, amount: u64, decimals: u8) -> Result<u64> {
+        let gross = amount as u128 * self.sell_price as u128 / 10u128.pow(decimals as u32);
+        let payout = gross * (BPS - self.treasury_share) as u128 / BPS as u128;
+        u64::try_from(payout).map_err(|_| errors::ErrorCode::MathOverflow.into())
+    }
+
+    //Number of days passed since the start
+    pub fn current_day(&self) -> Result<i64> {
+        let unix_now = Clock::get()?.unix_timestamp; //current time
+        Ok((unix_now - self.start)/self.sec_in_day)
+    }
+}
+
+#[account]
+pub struct Proposal { //pending change of the reward economy or the pool
+    pub proposer: Pubkey, //parameter manager that pays and gets back the rent
+    pub change: Change,
+    pub effective_at: i64, //unix timestamp execute_change is allowed from
+    pub bump: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum Change {
+    Params { economy: Economy },
+    NftMultiplier { common: u64, rare: u64, epic: u64, legendary: u64 },
+    Emission { per_day: u64 },
+    Pool { fee: u64, vault_share: u64 }, //BPS, the pool is passed through remaining_accounts
+}
@@ -0,0 +1,4 @@
+pub use config_state::*;
+pub use config_instructions::*;
+pub mod config_state;
+pub mod config_instructions;
@@ -4,26 +4,86 @@ pub const PUBKEY_MAX: usize = 32;
 pub const DISCRIMINATOR: usize = 8;
 pub const INT_SMALL: usize = 1;
 pub const VECTOR_SIZE: usize = 4;
-pub const MAX_PLAYERS: usize = 32;
+pub const MAX_PLAYERS: usize = 100;
+pub const NFT_QUALITIES: usize = 4; //common, rare, epic, legendary
+pub const MAX_SETTLE_BATCH: usize = 8; //player results per settle_game instruction, bounded by the transaction size
+pub const REWARD_HISTORY_LEN: usize = 60; //days kept in the RewardHistory ring buffer, bounded by the 10240 bytes an account can be allocated with
 
 //Consts for reward calculation
-pub const NFT_PRICE: u64 = 150000000000; //nft not implemented yet, const now
-pub const EULER_NUMBER: f64 = 2.718_281_828; //const e
-pub const MAX_SIZE_REWARD: usize = FLOAT_MAX*5 + DISCRIMINATOR; //Reward account four u64 fields and one i64 + discriminator
+pub const REWARD_SHARE_BASE: u64 = 300; //placement shares of Economy are parts of 300, so 1/4, 1/10 and 7/150 of the victory reward are exact
+pub const MAX_SIZE_REWARD: usize = NFT_QUALITIES*4*FLOAT_MAX + FLOAT_MAX + INT_SMALL + DISCRIMINATOR; //Reward account four u64 fields per quality and one i64 + bump + discriminator
+pub const MAX_SIZE_MULTIPLIER: usize = NFT_QUALITIES*FLOAT_MAX + INT_SMALL + DISCRIMINATOR; //QualityMultiplier u64 per quality + bump + discriminator
 pub const VICTORY: u64 = 16800000000; //calculate the rewards based on the nft price, values not final
-pub const TOP_FIVE: u64 = 67200000000;
-pub const TOP_TEN: u64= 168000000000;
-pub const KILL: u64 = 120000000000;
-pub const FEE_LAMPORTS: u64 = 100_000_000;
+pub const VICTORY_RARE: u64 = 14933333333; //scaled by the payback of each quality: common 1.2, rare 1.35, epic 1.5, legendary 2
+pub const VICTORY_EPIC: u64 = 13440000000;
+pub const VICTORY_LEGENDARY: u64 = 10080000000;
 
+//Fixed-point reward curve 1 - 1/(e^(10 - 0.5*x^(0.4*1.0005)) + 1), scaled by maths::fixed_point::SCALE
+pub const PRECISION: u64 = 1_000_000_000; //scale of the curve value and of fractional instruction arguments
+pub const CURVE_OFFSET: i128 = 10_000_000_000_000_000_000; //10
+pub const CURVE_SLOPE: u128 = 500_000_000_000_000_000; //0.5
+pub const CURVE_EXPONENT: u128 = 400_200_000_000_000_000; //0.4*1.0005
 
-//UNIX values
-pub const START: i64 = 1654797600; //smart contract start date, program not deployed yet, const now
-pub const SEC_IN_DAY: i64 = 86400; //seconds in day to calculate current day from the start
+//Weights of the placement chances in the nft multiplier, scaled by PRECISION
+pub const TOP_FIVE_WEIGHT: u128 = 250_000_000; //0.25
+pub const TOP_TEN_WEIGHT: u128 = 100_000_000; //0.1
+pub const KILL_WEIGHT: u128 = 46_700_000; //0.0467
+
+
+//Bounds of the economy parameters accepted by initialize_params and propose_params
+pub const MIN_SEC_IN_DAY: i64 = 3600;
+pub const MAX_SEC_IN_DAY: i64 = 7*86400;
+pub const MAX_NFT_PRICE: u64 = 1_000_000_000_000_000; //1M PLASMA
+pub const MAX_FEE_LAMPORTS: u64 = 1_000_000_000; //1 SOL
+pub const MAX_SELL_PRICE: u64 = 1_000_000_000; //1 SOL per PLASMA
+pub const BPS: u64 = 10_000; //basis points of the sell burn and treasury share
+pub const MIN_NFT_MULTIPLIER: u64 = PRECISION/10; //0.1, smaller values inflate the rewards and zero divides by zero
+pub const MAX_NFT_MULTIPLIER: u64 = 1_000*PRECISION; //1000, larger values round the rewards down to nothing
+pub const TIMELOCK_DELAY: i64 = 24*3600; //seconds between proposing a parameter change and executing it
+
+//Pause flags of Config.paused, one bit per instruction group
+pub const PAUSE_REWARDS: u8 = 1; //calculate_reward, settle_game
+pub const PAUSE_CLAIMS: u8 = 1 << 1; //user_claim
+pub const PAUSE_SELLS: u8 = 1 << 2; //user_sell
+pub const PAUSE_MINTING: u8 = 1 << 3; //mint_token and multisig mints
+pub const PAUSE_POOL: u8 = 1 << 4; //swap and add_liquidity
+pub const PAUSE_TREASURY: u8 = 1 << 5; //withdraw_treasury and multisig withdrawals
+pub const PAUSE_ALL: u8 = PAUSE_REWARDS | PAUSE_CLAIMS | PAUSE_SELLS | PAUSE_MINTING | PAUSE_POOL | PAUSE_TREASURY;
 
 //Player account
 pub const MAX_PLAYER_SIZE: usize = FLOAT_MAX + (1+FLOAT_MAX) + DISCRIMINATOR + FLOAT_MAX + PUBKEY_MAX + 5*FLOAT_MAX; //u64 + rating wrapped in some + discriminator for Player account + claimable i64 + pubkey
 pub const MAX_ACCOUNT_SIZE: usize = 10485760;
 
+//Config account
+pub const MAX_CONFIG_SIZE: usize = DISCRIMINATOR + 3*PUBKEY_MAX + 2*INT_SMALL + 5*FLOAT_MAX + (1 + PUBKEY_MAX) + 3*PUBKEY_MAX + INT_SMALL + FLOAT_MAX + INT_SMALL + FLOAT_MAX + 4*FLOAT_MAX + 2*FLOAT_MAX; //admin, mint and vault pubkeys + vault bump + config bump + mint caps and counters + pending admin wrapped in some + role keys + multisig flag and large mint + pause flags + total claimable + emission schedule + sell counters
+
+//Params account
+pub const MAX_PARAMS_SIZE: usize = DISCRIMINATOR + 12*FLOAT_MAX + INT_SMALL; //Economy + bump
+pub const MAX_PROPOSAL_SIZE: usize = DISCRIMINATOR + PUBKEY_MAX + INT_SMALL + 12*FLOAT_MAX + FLOAT_MAX + INT_SMALL; //proposer + Change tag + largest variant (Economy) + effective_at + bump
+
+//Multisig accounts
+pub const MAX_MULTISIG_OWNERS: usize = 10;
+pub const MAX_MULTISIG_SIZE: usize = DISCRIMINATOR + VECTOR_SIZE + MAX_MULTISIG_OWNERS*PUBKEY_MAX + INT_SMALL + FLOAT_MAX + FLOAT_MAX + INT_SMALL; //owners + threshold + nonce + owner set + bump
+pub const MAX_MULTISIG_TX_SIZE: usize = DISCRIMINATOR + FLOAT_MAX + PUBKEY_MAX + FLOAT_MAX + (INT_SMALL + 3*PUBKEY_MAX) + VECTOR_SIZE + MAX_MULTISIG_OWNERS*INT_SMALL + INT_SMALL; //nonce + proposer + owner set + action tag and largest variant (SetRoles) + approvals + bump
+
+//Liquidity pool
+pub const MAX_SWAP_FEE: u64 = 1_000; //10% in BPS
+pub const MINIMUM_LIQUIDITY: u64 = 1_000; //LP tokens locked by the first deposit so the pool never empties
+pub const LP_DECIMALS: u8 = 9;
+pub const MAX_POOL_SIZE: usize = DISCRIMINATOR + 3*PUBKEY_MAX + 2*FLOAT_MAX + INT_SMALL; //reserves and lp mint + fee and vault share + bump
+
 //PDA SEEDS
-pub const VAULT_PDA_SEED: &[u8] = b"vault";
\ No newline at end of file
+pub const VAULT_PDA_SEED: &[u8] = b"vault";
+pub const CONFIG_PDA_SEED: &[u8] = b"config";
+pub const REWARD_PDA_SEED: &[u8] = b"reward";
+pub const MULTIPLIER_PDA_SEED: &[u8] = b"multiplier";
+pub const REWARD_HISTORY_PDA_SEED: &[u8] = b"history";
+pub const PARAMS_PDA_SEED: &[u8] = b"params";
+pub const PROPOSAL_PDA_SEED: &[u8] = b"proposal";
+pub const TREASURY_PDA_SEED: &[u8] = b"treasury";
+pub const POOL_PDA_SEED: &[u8] = b"pool";
+pub const POOL_PLASMA_PDA_SEED: &[u8] = b"pool_plasma";
+pub const POOL_SOL_PDA_SEED: &[u8] = b"pool_sol";
+pub const POOL_LP_PDA_SEED: &[u8] = b"pool_lp";
+pub const MULTISIG_PDA_SEED: &[u8] = b"multisig";
+pub const MULTISIG_TX_PDA_SEED: &[u8] = b"multisig_tx";
@@ -7,5 +7,99 @@ pub enum ErrorCode {
     #[msg("The player's rating overflows")]
     RatingOverflow,
     #[msg("Too many players")]
-    PlayersOverflow
+    PlayersOverflow,
+    #[msg("Signer is not the configured storage")]
+    Unauthorized,
+    #[msg("Mint does not match the configured mint")]
+    InvalidMint,
+    #[msg("Token account is not the configured vault")]
+    InvalidVault,
+    #[msg("Player account does not belong to the signer")]
+    InvalidPlayer,
+    #[msg("Token account is not owned by the player")]
+    InvalidOwner,
+    #[msg("Minting would exceed the maximum supply")]
+    SupplyCapExceeded,
+    #[msg("Minting would exceed the daily emission budget")]
+    DailyMintCapExceeded,
+    #[msg("Math operation overflowed")]
+    MathOverflow,
+    #[msg("Settlement batch is empty, too large or does not match the player accounts")]
+    InvalidSettleBatch,
+    #[msg("Settlement batch does not continue from the settled players")]
+    SettleOutOfOrder,
+    #[msg("Game has already started")]
+    GameAlreadyStarted,
+    #[msg("Game is not running")]
+    GameNotRunning,
+    #[msg("Game has not ended or is already settled")]
+    GameNotEnded,
+    #[msg("Only a created, running or ended game can be cancelled")]
+    GameNotCancellable,
+    #[msg("Game roster is empty")]
+    EmptyRoster,
+    #[msg("Player is listed in the roster more than once")]
+    DuplicatePlayer,
+    #[msg("Player is not in the game roster")]
+    NotInRoster,
+    #[msg("Player is already rewarded for this game")]
+    AlreadyRewarded,
+    #[msg("Game is not settled")]
+    GameNotSettled,
+    #[msg("Only a settled or cancelled game can be closed")]
+    GameNotClosable,
+    #[msg("Reward table is stale, roll the reward day first")]
+    RewardTableStale,
+    #[msg("Reward table is already rolled for the current day")]
+    RewardTableUpToDate,
+    #[msg("Economy parameter is out of its allowed range")]
+    InvalidParams,
+    #[msg("Start and length of a day cannot change once the economy is initialized")]
+    ImmutableDays,
+    #[msg("Timelock of the proposed change has not expired yet")]
+    TimelockNotExpired,
+    #[msg("Signer is not the proposed admin")]
+    NotPendingAdmin,
+    #[msg("Signer does not hold the role required by the instruction")]
+    MissingRole,
+    #[msg("Mint and vault authorities must be held by the vault PDA")]
+    AuthorityNotTransferred,
+    #[msg("Instruction requires the approval of the multisig")]
+    MultisigRequired,
+    #[msg("Multisig owners, threshold or nonce are invalid")]
+    InvalidMultisig,
+    #[msg("Signer is not an owner of the multisig")]
+    NotMultisigOwner,
+    #[msg("Owner has already approved the transaction")]
+    AlreadyApproved,
+    #[msg("Transaction does not have enough approvals")]
+    NotEnoughApprovals,
+    #[msg("Target account does not match the multisig action")]
+    InvalidTarget,
+    #[msg("Transaction was proposed to an earlier set of multisig owners")]
+    StaleTransaction,
+    #[msg("Credit would push the claimable rewards past the vault balance and the emission allowance")]
+    InsufficientReserves,
+    #[msg("Vault cannot cover the claim within the emission schedule")]
+    EmissionScheduleExceeded,
+    #[msg("Claim amount is zero or exceeds the claimable reward")]
+    InvalidClaimAmount,
+    #[msg("Payout of the sell is below the minimum accepted by the player")]
+    SlippageExceeded,
+    #[msg("Selling would exceed the daily buyback limit")]
+    DailySellLimitExceeded,
+    #[msg("Treasury cannot cover the payout of the sell")]
+    TreasuryDepleted,
+    #[msg("Pool fee or vault share is out of its allowed range")]
+    InvalidPoolParams,
+    #[msg("Pool has no liquidity")]
+    PoolEmpty,
+    #[msg("Amount is too small to move any liquidity")]
+    ZeroLiquidity,
+    #[msg("Pool change needs the writable pool account")]
+    InvalidPool,
+    #[msg("Instruction group is paused")]
+    Paused,
+    #[msg("Pause flags contain an unknown instruction group")]
+    InvalidPauseFlags,
 }
\ No newline at end of file
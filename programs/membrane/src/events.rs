@@ -0,0 +1,315 @@
+use anchor_lang::prelude::*;
+use crate::config_state::{Change, Economy};
+use crate::maths::{Reward, RewardTable};
+use crate::multisig_state::MultisigAction;
+use crate::pool_state::SwapDirection;
+
+//Config
+#[event]
+pub struct ConfigInitialized {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub max_supply: u64,
+    pub daily_mint_cap: u64,
+    pub emission_per_day: u64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminChanged {
+    pub previous: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct RolesUpdated {
+    pub oracle: Pubkey,
+    pub treasurer: Pubkey,
+    pub param_manager: Pubkey,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub paused: u8, //PAUSE_* flags now in effect
+}
+
+#[event]
+pub struct SolvencyChecked {
+    pub total_claimable: u64,
+    pub vault_balance: u64,
+    pub reserves: u64, //vault balance and the emission allowance
+    pub solvent: bool, //the vault alone covers every claim
+}
+
+#[event]
+pub struct ParamsUpdated {
+    pub economy: Economy,
+}
+
+#[event]
+pub struct EmissionUpdated {
+    pub per_day: u64,
+    pub day: i64, //first day released at the rate
+}
+
+#[event]
+pub struct ChangeProposed {
+    pub change: Change,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct ChangeCancelled {
+    pub change: Change,
+}
+
+//Multisig
+#[event]
+pub struct MultisigInitialized {
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub large_mint: u64,
+}
+
+#[event]
+pub struct MultisigUpdated {
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct TransactionProposed {
+    pub nonce: u64,
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+}
+
+#[event]
+pub struct TransactionApproved {
+    pub nonce: u64,
+    pub owner: Pubkey,
+    pub approvals: u8, //approvals collected so far
+}
+
+#[event]
+pub struct TransactionCancelled {
+    pub nonce: u64,
+}
+
+#[event]
+pub struct TransactionExecuted {
+    pub nonce: u64,
+    pub action: MultisigAction,
+}
+
+//Rewards
+#[event]
+pub struct MultiplierUpdated {
+    pub common: u64,
+    pub rare: u64,
+    pub epic: u64,
+    pub legendary: u64,
+}
+
+#[event]
+pub struct RewardTableRolled { //reward tables in effect from the given day
+    pub days: i64,
+    pub common: RewardTable,
+    pub rare: RewardTable,
+    pub epic: RewardTable,
+    pub legendary: RewardTable,
+}
+
+impl From<&Reward> for RewardTableRolled {
+    fn from(reward: &Reward) -> Self {
+        RewardTableRolled {
+            days: reward.days,
+            common: reward.common,
+            rare: reward.rare,
+            epic: reward.epic,
+            legendary: reward.legendary,
+        }
+    }
+}
+
+#[event]
+pub struct RewardCredited {
+    pub identifier: u64, //game
+    pub player: Pubkey, //identity of the player
+    pub placement: u8,
+    pub kills: u8,
+    pub reward: u64,
+    pub rating: i64, //rating after the game
+    pub claimable: u64, //claimable after the game
+}
+
+#[event]
+pub struct RewardClaimed {
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+//Games
+#[event]
+pub struct GameCreated {
+    pub identifier: u64,
+    pub roster: Vec<Pubkey>,
+}
+
+#[event]
+pub struct GameStarted {
+    pub identifier: u64,
+    pub roster_size: u8,
+    pub start_timestamp: i64,
+}
+
+#[event]
+pub struct RosterExtended {
+    pub identifier: u64,
+    pub players: Vec<Pubkey>, //players added to the roster
+    pub roster_size: u8,
+}
+
+#[event]
+pub struct GameEnded {
+    pub identifier: u64,
+    pub end_timestamp: i64,
+}
+
+#[event]
+pub struct GameCancelled {
+    pub identifier: u64,
+    pub end_timestamp: i64,
+}
+
+#[event]
+pub struct GameArchived {
+    pub identifier: u64,
+    pub results_hash: [u8; 32],
+    pub total_reward: u64,
+    pub player_count: u8,
+}
+
+#[event]
+pub struct GameClosed {
+    pub identifier: u64,
+}
+
+//Players
+#[event]
+pub struct PlayerInitialized {
+    pub player: Pubkey,
+    pub rating: i64,
+    pub fee: u64, //lamports paid to storage
+}
+
+#[event]
+pub struct PlayerUpdated {
+    pub player: Pubkey,
+    pub nft_counter: u64,
+}
+
+//Token
+#[event]
+pub struct TokensMinted {
+    pub to: Pubkey, //token account
+    pub amount: u64,
+    pub total_minted: u64,
+    pub minted_today: u64,
+}
+
+#[event]
+pub struct VaultReplenished {
+    pub amount: u64, //minted into the vault
+    pub emitted: u64, //cumulative emission after the top up
+    pub minted_today: u64, //daily budget used, shared with mint_token
+    pub day: i64,
+}
+
+#[event]
+pub struct TokensSold {
+    pub player: Pubkey,
+    pub amount: u64, //transferred to the vault
+    pub burned: u64,
+    pub payout: u64, //lamports paid by the treasury
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub to: Pubkey,
+    pub amount: u64, //lamports
+    pub balance: u64, //lamports left in the treasury
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub authority: Pubkey, //new mint, freeze and vault owner authority
+}
+
+#[event]
+pub struct AuthorityReturned {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub lamports: u64, //swept from the vault PDA
+}
+
+#[event]
+pub struct StorageFrozen {
+    pub mint: Pubkey,
+    pub account: Pubkey, //frozen token account
+}
+
+#[event]
+pub struct StorageThawed {
+    pub mint: Pubkey,
+    pub account: Pubkey, //thawed token account
+}
+
+//Pool
+#[event]
+pub struct PoolInitialized {
+    pub lp_mint: Pubkey,
+    pub fee: u64,
+    pub vault_share: u64,
+}
+
+#[event]
+pub struct PoolUpdated {
+    pub fee: u64,
+    pub vault_share: u64,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub provider: Pubkey,
+    pub plasma: u64,
+    pub sol: u64, //wrapped SOL
+    pub lp: u64, //minted to the provider
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub provider: Pubkey,
+    pub plasma: u64,
+    pub sol: u64,
+    pub lp: u64, //burned from the provider
+}
+
+#[event]
+pub struct Swapped {
+    pub trader: Pubkey,
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_to_vault: u64, //PLASMA
+    pub fee_burned: u64, //PLASMA
+}
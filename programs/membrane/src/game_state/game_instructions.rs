@@ -1,13 +1,28 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, Mint, TokenAccount, Transfer, Approve};
+use anchor_spl::associated_token::{self, get_associated_token_address, AssociatedToken, Create};
+use anchor_spl::token::{self, Token, Mint, TokenAccount, Transfer};
 use crate::player_state;
+use crate::token_state;
 use crate::errors;
 use crate::maths;
+use crate::config_state::{Config, Params};
+use crate::events::{MultiplierUpdated, RewardClaimed, RewardCredited, RewardTableRolled};
 pub use crate::constants::{self, *};
 
 pub fn initialize_nft_multiplier(ctx: Context<InitializeMultiplier>) -> Result<()> {
     let nft_multipler = &mut ctx.accounts.nft_multiplier;
     nft_multipler.common = constants::VICTORY; //value at the beginning of the game when no user statistics is available
+    nft_multipler.rare = constants::VICTORY_RARE;
+    nft_multipler.epic = constants::VICTORY_EPIC;
+    nft_multipler.legendary = constants::VICTORY_LEGENDARY;
+    nft_multipler.bump = *ctx.bumps.get("nft_multiplier").unwrap();
+
+    emit!(MultiplierUpdated {
+        common: nft_multipler.common,
+        rare: nft_multipler.rare,
+        epic: nft_multipler.epic,
+        legendary: nft_multipler.legendary,
+    });
 
     Ok(())
 }
@@ -16,24 +31,63 @@ pub fn initialize_reward(ctx: Context<maths::InitializeReward>) -> Result<()> {
     let reward_account = &mut ctx.accounts.reward;
     let nft_multipler = &mut ctx.accounts.nft_multiplier;
 
-    reward_account.days = 0; //set days to 0
-    reward_account.calculate_reward(nft_multipler.common);
+    reward_account.bump = *ctx.bumps.get("reward").unwrap();
+    let multiplier = reward_account.calculate_reward(nft_multipler, &ctx.accounts.params.economy)?; //sets days to the current day
+
+    let history = &mut ctx.accounts.history.load_init()?;
+    history.bump = *ctx.bumps.get("history").unwrap();
+    history.record(reward_account, multiplier);
+
+    emit!(RewardTableRolled::from(&**reward_account));
 
     Ok(())
 }
 
-//payback is a multiplier of how much a user should receive upon fully exhausting the nft based on its quality (e.g 1.2 for common, 1.5 for epic, 2 for leg)
-pub fn update_nft_multiplier(ctx: Context<UpdateMultiplier>, stats: AvgStats, nfts: NftQualities) -> Result<()> { //TODO: Change payback and durability from number to struct for each quality
-    let nft_multiplier = &mut ctx.accounts.nft_multiplier;
+//Fn to move the reward table to the next day, anyone can call it once a day
+pub fn roll_reward_day(ctx: Context<maths::RollRewardDay>) -> Result<()> {
+    let reward_account = &mut ctx.accounts.reward;
+    let economy = &ctx.accounts.params.economy;
+    if reward_account.days == economy.current_day()? {
+        return Err(errors::ErrorCode::RewardTableUpToDate.into())
+    }
 
-    let stats_coefficient = stats.league * (0.25 * stats.topfive + 0.1 * stats.topten + stats.victory + 0.0467 * stats.kills);
-    let nft_coefficient = (nfts.common.durability as f64) / (nfts.common.payback);
+    let multiplier = reward_account.calculate_reward(&ctx.accounts.nft_multiplier, economy)?;
+    ctx.accounts.history.load_mut()?.record(reward_account, multiplier);
 
-    nft_multiplier.common = (stats_coefficient * nft_coefficient * 10.0_f64.powf(9.0)) as u64;
+    emit!(RewardTableRolled::from(&**reward_account));
 
     Ok(())
 }
 
+//Multipliers of common, rare, epic and legendary nfts, applied through the timelocked propose_nft_multiplier
+//payback is a multiplier of how much a user should receive upon fully exhausting the nft based on its quality (e.g 1.2 for common, 1.5 for epic, 2 for leg)
+pub fn nft_multipliers(stats: &AvgStats, nfts: &NftQualities) -> Result<[u64; NFT_QUALITIES]> {
+    let precision = constants::PRECISION as u128;
+
+    //league * (0.25 * topfive + 0.1 * topten + victory + 0.0467 * kills), every input and the result scaled by PRECISION, rounded down
+    let weighted_chances = (TOP_FIVE_WEIGHT * stats.topfive as u128
+        + TOP_TEN_WEIGHT * stats.topten as u128
+        + KILL_WEIGHT * stats.kills as u128) / precision
+        + stats.victory as u128;
+    let stats_coefficient = stats.league as u128 * weighted_chances / precision;
+
+    Ok([
+        quality_multiplier(stats_coefficient, &nfts.common)?,
+        quality_multiplier(stats_coefficient, &nfts.rare)?,
+        quality_multiplier(stats_coefficient, &nfts.epic)?,
+        quality_multiplier(stats_coefficient, &nfts.legendary)?,
+    ])
+}
+
+//stats_coefficient * durability / payback, scaled by PRECISION, rounded down
+fn quality_multiplier(stats_coefficient: u128, nft: &NftStats) -> Result<u64> {
+    stats_coefficient
+        .checked_mul(nft.durability as u128 * constants::PRECISION as u128)
+        .and_then(|value| value.checked_div(nft.payback as u128))
+        .and_then(|value| u64::try_from(value).ok())
+        .ok_or_else(|| errors::ErrorCode::MathOverflow.into())
+}
+
 //Fn to calculate reward at the end of the game and update player account
 // pub fn calculate_reward(ctx: Context<CalculateReward>, placement: u64, kills: u64, _identifier: u64) -> Result<()> {
 //     let stat = Stats {
@@ -56,12 +110,101 @@ pub fn update_nft_multiplier(ctx: Context<UpdateMultiplier>, stats: AvgStats, nf
 
 pub fn calculate_reward(
     ctx: Context<CalculateReward>,
-    placement: u64,
-    kills: u64,
+    placement: u8,
+    kills: u8,
+    quality: maths::NftQuality,
     _identifier: u64,
     _bump: u8,
 ) -> Result<()> {
-    let player = &mut ctx.accounts.player;
+    ctx.accounts.config.ensure_unpaused(PAUSE_REWARDS)?;
+
+    let game = &mut ctx.accounts.game.load_mut()?;
+    if !game.is(GameStatus::Ended) { //results are credited only after the game has ended
+        return Err(errors::ErrorCode::GameNotEnded.into())
+    }
+
+    let reward_account = &ctx.accounts.reward; //define Reward account
+    reward_account.ensure_current(&ctx.accounts.params.economy)?; //roll_reward_day has to run first once the next day has begun
+    let reward_table = reward_account.table(quality); //reward table of the nft quality the player used
+
+    let day = ctx.accounts.params.economy.current_day()?;
+    let stat = credit_player(&mut ctx.accounts.player, reward_table, game.identifier, placement, kills)?;
+    ctx.accounts.config.record_credit(stat.reward, ctx.accounts.vault_token.amount, ctx.accounts.mint.supply, day)?;
+    game.record(stat)?;
+
+    let shortfall = ctx.accounts.config.total_claimable.saturating_sub(ctx.accounts.vault_token.amount);
+    token_state::replenish_vault(
+        &mut ctx.accounts.config,
+        &ctx.accounts.mint,
+        ctx.accounts.vault_token.to_account_info(),
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        day,
+        shortfall,
+    )?;
+
+    Ok(())
+}
+
+//Fn to settle a chunk of the game results at once, player accounts are passed through remaining_accounts in the order of results
+pub fn settle_game<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleGame<'info>>,
+    _identifier: u64,
+    _bump: u8,
+    offset: u8,
+    results: Vec<PlayerResult>,
+) -> Result<()> {
+    ctx.accounts.config.ensure_unpaused(PAUSE_REWARDS)?;
+
+    if results.is_empty() || results.len() > MAX_SETTLE_BATCH || results.len() != ctx.remaining_accounts.len() {
+        return Err(errors::ErrorCode::InvalidSettleBatch.into())
+    }
+
+    let game = &mut ctx.accounts.game.load_mut()?;
+    if !game.is(GameStatus::Ended) {
+        return Err(errors::ErrorCode::GameNotEnded.into())
+    }
+    if offset != game.settled { //chunks are settled in order and only once
+        return Err(errors::ErrorCode::SettleOutOfOrder.into())
+    }
+
+    let reward_account = &ctx.accounts.reward;
+    reward_account.ensure_current(&ctx.accounts.params.economy)?;
+    let day = ctx.accounts.params.economy.current_day()?;
+
+    for (result, account) in results.iter().zip(ctx.remaining_accounts.iter()) {
+        if !account.is_writable {
+            return Err(errors::ErrorCode::InvalidPlayer.into())
+        }
+        let mut player: Account<player_state::Player> = Account::try_from(account)?; //checks the owner and the discriminator
+        if player.identity != result.player {
+            return Err(errors::ErrorCode::InvalidPlayer.into())
+        }
+
+        let reward_table = reward_account.table(result.quality);
+        let stat = credit_player(&mut player, reward_table, game.identifier, result.placement, result.kills)?;
+        player.exit(ctx.program_id)?; //persist the player account
+
+        ctx.accounts.config.record_credit(stat.reward, ctx.accounts.vault_token.amount, ctx.accounts.mint.supply, day)?;
+        game.record(stat)?;
+    }
+
+    let shortfall = ctx.accounts.config.total_claimable.saturating_sub(ctx.accounts.vault_token.amount); //top up once per batch
+    token_state::replenish_vault(
+        &mut ctx.accounts.config,
+        &ctx.accounts.mint,
+        ctx.accounts.vault_token.to_account_info(),
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        day,
+        shortfall,
+    )?;
+
+    Ok(())
+}
+
+//Update rating and claimable of the player for a single game result, returns the stat to record in the game
+fn credit_player(player: &mut player_state::Player, reward_table: &maths::RewardTable, identifier: u64, placement: u8, kills: u8) -> Result<Stats> {
     let rating_multiplier:u64 = match player.rating { //match rating_multiplier
         Some(0..=100) => 8, //values not final
         Some(101..=200) => 9,
@@ -69,17 +212,6 @@ pub fn calculate_reward(
         None => return Err(errors::ErrorCode::RatingUndefined.into()),
         _ => return Err(errors::ErrorCode::RatingOverflow.into()),
     };
-    let reward_account = &mut ctx.accounts.reward; //define Reward account
-    let nft_multiplier = &mut ctx.accounts.nft_multiplier;
-
-    let unix_now = Clock::get().unwrap().unix_timestamp; //current time to compare
-
-    if ((unix_now - constants::START)/constants::SEC_IN_DAY) != reward_account.days { //if statement to check whether next day has begun
-        reward_account.calculate_reward(nft_multiplier.common); //Calculate and update the reward account //only common quality at the moment
-        reward_account.reload()?; //update the reward account if new day begun
-        reward_account.days = (unix_now - constants::START)/constants::SEC_IN_DAY;
-    }
-
 
     //Define placement_reward based on placement
     let placement_reward = match placement { //match the player placement and update player account
@@ -87,19 +219,19 @@ pub fn calculate_reward(
             player.rating = Some(player
                 .rating
                 .unwrap() + 10); //values not final
-            reward_account.victory
+            reward_table.victory
         },
         2..=5 => {
             player.rating = Some(player
                 .rating
                 .unwrap() + 5);
-            reward_account.top_five
+            reward_table.top_five
         },
         6..=10 => {
             player.rating = Some(player
                 .rating
                 .unwrap() + 2);
-            reward_account.top_ten
+            reward_table.top_ten
         },
         _ => {
             player.rating = Some(player
@@ -114,57 +246,88 @@ pub fn calculate_reward(
         },
     };
 
-    let kill_reward = kills * reward_account.kill; //calculate total reward for kills
-    let reward = (rating_multiplier * (placement_reward + kill_reward))/10; //calculate total reward
-    player.claimable = player.claimable + reward;
+    let reward = reward_table.kill
+        .checked_mul(kills as u64) //calculate total reward for kills
+        .and_then(|kill_reward| kill_reward.checked_add(placement_reward))
+        .and_then(|value| value.checked_mul(rating_multiplier))
+        .map(|value| value/10) //calculate total reward
+        .ok_or(errors::ErrorCode::MathOverflow)?;
+    player.claimable = player.claimable.checked_add(reward).ok_or(errors::ErrorCode::MathOverflow)?;
+
+    emit!(RewardCredited {
+        identifier,
+        player: player.identity,
+        placement,
+        kills,
+        reward,
+        rating: player.rating.unwrap(),
+        claimable: player.claimable,
+    });
 
-    let stat = Stats {
+    Ok(Stats {
         id: player.identity,
-        placement: placement as u8,
-        kills: kills as u8,
+        placement,
+        kills,
         // survival_duration: game.timestamp, //change later not implemented yet
         reward,
-
-    };
-
-    let game = &mut ctx.accounts.game;
-    game.players.push(stat);
-    
-
-    Ok(())
+    })
 }
 
 
-//Fn to delegate claimable token to the user
+//Fn to transfer the whole claimable reward to the user
 pub fn user_claim(ctx: Context<UserClaim>) -> Result<()> {
-    let (_vault_authority, vault_authority_bump) = Pubkey::find_program_address(&[VAULT_PDA_SEED], ctx.program_id);
-    let authority_seeds = &[&VAULT_PDA_SEED[..], &[vault_authority_bump]];
-    let seeds = &[&authority_seeds[..]];
+    let amount = ctx.accounts.player.claimable;
+    claim(ctx, amount)
+}
 
-    let player = &mut ctx.accounts.player;
+//Fn to withdraw a part of the claimable reward, signed by the vault PDA so no delegate is left on the vault
+pub fn claim(ctx: Context<UserClaim>, amount: u64) -> Result<()> {
+    ctx.accounts.config.ensure_unpaused(PAUSE_CLAIMS)?;
+
+    if ctx.accounts.player_token.data_is_empty() { //only the associated token account of the user is created
+        if ctx.accounts.player_token.key() != get_associated_token_address(ctx.accounts.user.key, &ctx.accounts.mint.key()) {
+            return Err(errors::ErrorCode::InvalidOwner.into())
+        }
+        let cpi_accounts = Create {
+            payer: ctx.accounts.user.to_account_info(),
+            associated_token: ctx.accounts.player_token.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.associated_token_program.to_account_info();
+        associated_token::create(CpiContext::new(cpi_program, cpi_accounts))?;
+    }
+    let player_token: Account<TokenAccount> = Account::try_from(&ctx.accounts.player_token)?; //checks the token program owns it
+    if player_token.owner != ctx.accounts.user.key() {
+        return Err(errors::ErrorCode::InvalidOwner.into())
+    }
+    if player_token.mint != ctx.accounts.mint.key() {
+        return Err(errors::ErrorCode::InvalidMint.into())
+    }
 
-    //Define Approve account
-    let cpi_accounts = Approve {
-        delegate: ctx
-        .accounts
-        .user
-        .to_account_info(),
+    if amount == 0 || amount > ctx.accounts.player.claimable {
+        return Err(errors::ErrorCode::InvalidClaimAmount.into())
+    }
 
-        to: ctx
-        .accounts
-        .vault_token
-        .to_account_info(),
+    let shortfall = amount.saturating_sub(ctx.accounts.vault_token.amount);
+    let replenished = token_state::replenish_vault(
+        &mut ctx.accounts.config,
+        &ctx.accounts.mint,
+        ctx.accounts.vault_token.to_account_info(),
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.params.economy.current_day()?,
+        shortfall,
+    )?;
+    if replenished < shortfall {
+        return Err(errors::ErrorCode::EmissionScheduleExceeded.into())
+    }
 
-        authority: ctx
-        .accounts
-        .authority
-        .to_account_info(),
-    };
-    //Define token program
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    //Define CpiContext<Approve> with PDA signer seeds
-    let cpi_ctx= CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds);
-    token::approve(cpi_ctx, player.claimable)?;
+    let authority_seeds = &[&VAULT_PDA_SEED[..], &[ctx.accounts.config.vault_bump]];
+    let seeds = &[&authority_seeds[..]];
 
     //Define Transfer account
     let cpi_accounts = Transfer {
@@ -180,129 +343,254 @@ pub fn user_claim(ctx: Context<UserClaim>) -> Result<()> {
 
         authority: ctx
         .accounts
-        .user
+        .authority
         .to_account_info(),
     };
 
     //Define token program
     let cpi_program = ctx.accounts.token_program.to_account_info();
-    //Define CpiContext<Transfer>
-    let cpi_ctx= CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, player.claimable)?;
-
-    player.claimable = 0;
-
-    //not sure if revoke is necessary, since solana can automatically change the delegated_amount, test needed
-    // //Define Revoke account
-    // let cpi_accounts = Revoke {
-    //     source: ctx
-    //     .accounts
-    //     .user
-    //     .to_account_info(),
-    //     authority: ctx
-    //     .accounts
-    //     .authority
-    //     .to_account_info(),
-    // };
-    // let cpi_program = ctx.accounts.token_program.to_account_info();
-    // let cpi_ctx= CpiContext::new(cpi_program, cpi_accounts);
-    // token::revoke(cpi_ctx)?;
+    //Define CpiContext<Transfer> with PDA signer seeds
+    let cpi_ctx= CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds);
+    token::transfer(cpi_ctx, amount)?;
+
+    ctx.accounts.config.record_claim(amount);
+    let player = &mut ctx.accounts.player;
+    player.claimable -= amount;
+
+    emit!(RewardClaimed {
+        player: player.identity,
+        amount,
+    });
 
     Ok(())
 }
 
 #[derive(Accounts)]
 pub struct InitializeMultiplier<'info> {
-        #[account(init, payer = payer, space = 5000)] //change space after all qualities are added
+        #[account(init, payer = payer, space = MAX_SIZE_MULTIPLIER, seeds = [MULTIPLIER_PDA_SEED], bump)]
         pub nft_multiplier: Account<'info, maths::QualityMultiplier>,
         #[account(mut)]
         pub payer: Signer<'info>,
+        #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == payer.key() @ errors::ErrorCode::Unauthorized)]
+        pub config: Account<'info, Config>,
         pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(placement: u64, kills: u64, identifier: u64, bump: u8)]
+#[instruction(placement: u8, kills: u8, quality: maths::NftQuality, identifier: u64, bump: u8)]
 pub struct CalculateReward<'info> {
-    #[account(mut)]
+    #[account(seeds = [REWARD_PDA_SEED], bump = reward.bump)]
     pub reward: Account<'info, maths::Reward>,
     #[account(mut)]
     player: Account<'info, player_state::Player>,
     #[account(mut)]
     pub storage: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.oracle == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
     #[account(mut, seeds = [b"game".as_ref(), identifier.to_string().as_bytes()], bump = bump)]
-    pub game: Account<'info, Game>,
-    pub nft_multiplier: Account<'info, maths::QualityMultiplier>,
+    pub game: AccountLoader<'info, Game>,
+    #[account(seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
+    #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
+    pub vault_token: Account<'info, TokenAccount>, //reserves backing the credits
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
+    pub authority: AccountInfo<'info>, //PDA, mints the vault top ups
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(identifier: u64, bump: u8)]
+pub struct SettleGame<'info> {
+    #[account(seeds = [REWARD_PDA_SEED], bump = reward.bump)]
+    pub reward: Account<'info, maths::Reward>,
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.oracle == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"game".as_ref(), identifier.to_string().as_bytes()], bump = bump)]
+    pub game: AccountLoader<'info, Game>,
+    #[account(seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
+    #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
+    pub vault_token: Account<'info, TokenAccount>, //reserves backing the credits
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
+    pub authority: AccountInfo<'info>, //PDA, mints the vault top ups
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct UserClaim<'info> {
-        #[account(mut)]
+        #[account(mut, seeds = [b"player".as_ref(), user.key().as_ref()], bump, constraint = player.identity == user.key() @ errors::ErrorCode::InvalidPlayer)]
         pub player: Account<'info, player_state::Player>,
         #[account(mut)]
         pub user: Signer<'info>,
         /// CHECK: SAFE PROGRAM OWNED ACCOUNT
-        #[account(mut)]
+        #[account(mut, seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
         pub authority: AccountInfo<'info>, //PDA
-        #[account(mut)]
+        #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
         pub vault_token: Account<'info, TokenAccount>,
+        /// CHECK: SAFE, any token account of the user, the associated one is created when it does not exist
         #[account(mut)]
-        pub player_token: Account<'info, TokenAccount>,
+        pub player_token: UncheckedAccount<'info>,
+        #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
         pub mint: Account<'info, Mint>,
+        #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump)]
+        pub config: Account<'info, Config>,
+        #[account(seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+        pub params: Account<'info, Params>,
         pub token_program: Program<'info, Token>,
+        pub associated_token_program: Program<'info, AssociatedToken>,
+        pub system_program: Program<'info, System>,
+        pub rent: Sysvar<'info, Rent>,
 }
 
-#[account]
-#[derive(Default)]
-pub struct Game { //1364
-    pub players: Vec<Stats>, //4 + 1344
+#[account(zero_copy)]
+#[repr(C, packed)]
+pub struct Game { //7443 with 100 players, loaded in place so the size does not cost compute
     pub identifier: u64,
     pub start_timestamp: i64,
     pub end_timestamp: i64,
+    pub total_reward: u64, //sum of the rewards credited in the game
+    pub roster_size: u8, //number of players in the lobby
+    pub settled: u8, //number of players whose results are settled
+    pub status: u8, //GameStatus
+    pub roster: [Pubkey; MAX_PLAYERS], //identities of the players in the lobby
+    pub players: [Stats; MAX_PLAYERS], //result of roster[i] is kept in players[i], zeroed until settled
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum GameStatus {
+    #[default]
+    Created, //set by create_game, the roster can still be extended
+    Running, //set by start_game, the roster is fixed
+    Ended, //waiting for the results
+    Settled, //every player is credited
+    Cancelled,
 }
 
 impl Game {
-    pub const STATS_LEN: usize = (FLOAT_MAX + PUBKEY_MAX + INT_SMALL + INT_SMALL);
-    pub const MISC_LEN: usize =  DISCRIMINATOR + 
-    VECTOR_SIZE +
+    pub fn is(&self, status: GameStatus) -> bool {
+        self.status == status as u8
+    }
+
+    pub fn set_status(&mut self, status: GameStatus) {
+        self.status = status as u8;
+    }
+
+    //Add players to the lobby, rejecting duplicates and lobbies over MAX_PLAYERS
+    pub fn register(&mut self, roster: &[Pubkey]) -> Result<()> {
+        let size = self.roster_size as usize;
+        if size + roster.len() > MAX_PLAYERS {
+            return Err(errors::ErrorCode::PlayersOverflow.into())
+        }
+        for (i, player) in roster.iter().enumerate() {
+            if *player == Pubkey::default() { //a zeroed identity marks an unsettled result
+                return Err(errors::ErrorCode::InvalidPlayer.into())
+            }
+            if self.roster[..size].contains(player) || roster[..i].contains(player) {
+                return Err(errors::ErrorCode::DuplicatePlayer.into())
+            }
+        }
+
+        self.roster[size..size + roster.len()].copy_from_slice(roster);
+        self.roster_size += roster.len() as u8;
+        Ok(())
+    }
+
+    //Record the result of a roster member, at most once per player, and move the game to Settled once every player is credited
+    pub fn record(&mut self, stat: Stats) -> Result<()> {
+        let index = self.roster[..self.roster_size as usize]
+            .iter()
+            .position(|player| *player == stat.id)
+            .ok_or(errors::ErrorCode::NotInRoster)?;
+        if self.players[index].id != Pubkey::default() {
+            return Err(errors::ErrorCode::AlreadyRewarded.into())
+        }
+
+        self.players[index] = stat;
+        self.settled += 1;
+        self.total_reward = self.total_reward.checked_add(stat.reward).ok_or(errors::ErrorCode::MathOverflow)?;
+        if self.settled == self.roster_size {
+            self.set_status(GameStatus::Settled);
+        }
+        Ok(())
+    }
+
+    //Settled results in the roster order
+    pub fn results(&self) -> &[Stats] {
+        &self.players[..self.roster_size as usize]
+    }
+
+    pub const LEN: usize = DISCRIMINATOR + std::mem::size_of::<Game>();
+}
+
+#[account]
+pub struct GameArchive { //summary kept after the game account is closed
+    pub identifier: u64,
+    pub results_hash: [u8; 32], //sha256 of the settled Game.players
+    pub total_reward: u64,
+    pub player_count: u8,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub bump: u8,
+}
+
+impl GameArchive {
+    pub const LEN: usize = DISCRIMINATOR +
+    FLOAT_MAX +
+    PUBKEY_MAX + //hash
     FLOAT_MAX +
-    FLOAT_MAX + 
-    FLOAT_MAX;
+    INT_SMALL +
+    FLOAT_MAX +
+    FLOAT_MAX +
+    INT_SMALL;
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
-pub struct Stats { //(32 + 1 + 1 + 8) * 32 = 1344
+#[zero_copy]
+#[repr(C, packed)]
+#[derive(Debug, Default)]
+pub struct Stats { //(32 + 1 + 1 + 8) * 100 = 4200
     pub reward: u64, //8
     pub id: Pubkey, //32
     pub placement: u8, //1
     pub kills: u8, //1
 }
 
-#[derive(Accounts)]
-pub struct UpdateMultiplier<'info> {
-    #[account(mut)]
-    pub nft_multiplier: Account<'info, maths::QualityMultiplier>,
-    #[account(mut)]
-    pub storage: Signer<'info>,
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct PlayerResult { //result of a single player sent to settle_game
+    pub player: Pubkey, //identity of the player
+    pub placement: u8,
+    pub kills: u8,
+    pub quality: maths::NftQuality,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
-pub struct AvgStats { //shows the chance for placement and avg kills per game
-    pub league: f64, //average rating league multiplier
-    pub victory: f64, //1
-    pub topfive: f64, //2-5
-    pub topten: f64, //6-10
-    pub kills: f64,
+pub struct AvgStats { //shows the chance for placement and avg kills per game, all values scaled by PRECISION
+    pub league: u64, //average rating league multiplier
+    pub victory: u64, //1
+    pub topfive: u64, //2-5
+    pub topten: u64, //6-10
+    pub kills: u64,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct NftQualities {
     pub common: NftStats,
-    //add other qualities
+    pub rare: NftStats,
+    pub epic: NftStats,
+    pub legendary: NftStats,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct NftStats {
     pub durability: u64,
-    pub payback: f64,
+    pub payback: u64, //scaled by PRECISION
 }
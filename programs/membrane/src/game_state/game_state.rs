@@ -1,52 +1,203 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash;
 // use anchor_spl::token::{self, Token, Mint, TokenAccount, Transfer, Approve};
 // use crate::player_state;
 use crate::errors;
+use crate::events::{GameArchived, GameCancelled, GameClosed, GameCreated, GameEnded, GameStarted, RosterExtended};
 // use crate::maths;
-pub use crate::constants;
-use super::Game;
+pub use crate::constants::CONFIG_PDA_SEED;
+use crate::config_state::Config;
+use super::{Game, GameArchive, GameStatus};
 
-pub fn start_game(ctx: Context<StartGame>, identifier: u64, players: u8) -> Result<()> {
+pub fn create_game(ctx: Context<CreateGame>, identifier: u64, roster: Vec<Pubkey>) -> Result<()> { //roster holds identities of the players in the lobby
+    let game = &mut ctx.accounts.game.load_init()?;
+    game.register(&roster)?;
+    game.identifier = identifier;
+    game.set_status(GameStatus::Created);
+
+    emit!(GameCreated {
+        identifier,
+        roster,
+    });
+
+    Ok(())
+}
+
+//Fn to register the rest of a lobby that does not fit into the create_game transaction
+pub fn extend_roster(ctx: Context<UpdateGame>, _identifier: u64, _bump: u8, roster: Vec<Pubkey>) -> Result<()> {
+    let game = &mut ctx.accounts.game.load_mut()?;
+    if !game.is(GameStatus::Created) { //the roster is fixed once the game starts
+        return Err(errors::ErrorCode::GameAlreadyStarted.into())
+    }
+    game.register(&roster)?;
 
-    if players > constants::MAX_PLAYERS as u8 {
-        return Err(errors::ErrorCode::PlayersOverflow.into())
+    emit!(RosterExtended {
+        identifier: game.identifier,
+        players: roster,
+        roster_size: game.roster_size,
+    });
+
+    Ok(())
+}
+
+pub fn start_game(ctx: Context<UpdateGame>, _identifier: u64, _bump: u8) -> Result<()> {
+    let game = &mut ctx.accounts.game.load_mut()?;
+    if !game.is(GameStatus::Created) {
+        return Err(errors::ErrorCode::GameAlreadyStarted.into())
+    }
+    if game.roster_size == 0 { //a game without players could never be settled
+        return Err(errors::ErrorCode::EmptyRoster.into())
     }
 
-    let game = &mut ctx.accounts.game;
     game.start_timestamp = Clock::get().unwrap().unix_timestamp;
-    game.identifier = identifier;
+    game.set_status(GameStatus::Running);
+
+    emit!(GameStarted {
+        identifier: game.identifier,
+        roster_size: game.roster_size,
+        start_timestamp: game.start_timestamp,
+    });
 
     Ok(())
 }
 
-pub fn end_game(ctx: Context<EndGame>, _identifier: u64, _bump: u8) -> Result<()> {
-    let game = &mut ctx.accounts.game;
+pub fn end_game(ctx: Context<UpdateGame>, _identifier: u64, _bump: u8) -> Result<()> {
+    let game = &mut ctx.accounts.game.load_mut()?;
+    if !game.is(GameStatus::Running) {
+        return Err(errors::ErrorCode::GameNotRunning.into())
+    }
+
     game.end_timestamp = Clock::get().unwrap().unix_timestamp;
+    game.set_status(GameStatus::Ended);
+
+    emit!(GameEnded {
+        identifier: game.identifier,
+        end_timestamp: game.end_timestamp,
+    });
     
     Ok(())
 }
 
+//Fn to give up on a game, an ended game whose roster cannot be fully settled is cancelled so it can be closed
+pub fn cancel_game(ctx: Context<UpdateGame>, _identifier: u64, _bump: u8) -> Result<()> {
+    let game = &mut ctx.accounts.game.load_mut()?;
+    if !game.is(GameStatus::Created) && !game.is(GameStatus::Running) && !game.is(GameStatus::Ended) {
+        return Err(errors::ErrorCode::GameNotCancellable.into())
+    }
+
+    if !game.is(GameStatus::Ended) { //an ended game keeps the time it ended at
+        game.end_timestamp = Clock::get().unwrap().unix_timestamp;
+    }
+    game.set_status(GameStatus::Cancelled);
+
+    emit!(GameCancelled {
+        identifier: game.identifier,
+        end_timestamp: game.end_timestamp,
+    });
+
+    Ok(())
+}
+
+//Fn to keep a compact summary of a settled game before it is closed
+pub fn archive_game(ctx: Context<ArchiveGame>, _identifier: u64, _bump: u8) -> Result<()> {
+    let game = &ctx.accounts.game.load()?;
+    if !game.is(GameStatus::Settled) {
+        return Err(errors::ErrorCode::GameNotSettled.into())
+    }
+
+    let mut hasher = hash::Hasher::default(); //sha256 of reward, id, placement and kills of every result in the roster order
+    for stat in game.results() {
+        hasher.hash(&stat.reward.to_le_bytes());
+        hasher.hash(stat.id.as_ref());
+        hasher.hash(&[stat.placement, stat.kills]);
+    }
+
+    let archive = &mut ctx.accounts.archive;
+    archive.identifier = game.identifier;
+    archive.results_hash = hasher.result().to_bytes();
+    archive.total_reward = game.total_reward;
+    archive.player_count = game.settled;
+    archive.start_timestamp = game.start_timestamp;
+    archive.end_timestamp = game.end_timestamp;
+    archive.bump = *ctx.bumps.get("archive").unwrap();
+
+    emit!(GameArchived {
+        identifier: archive.identifier,
+        results_hash: archive.results_hash,
+        total_reward: archive.total_reward,
+        player_count: archive.player_count,
+    });
+
+    Ok(())
+}
+
+//Fn to close a finished game and return the rent to storage
+pub fn close_game(ctx: Context<CloseGame>, _identifier: u64, _bump: u8) -> Result<()> {
+    let game = &ctx.accounts.game.load()?;
+    if !game.is(GameStatus::Settled) && !game.is(GameStatus::Cancelled) {
+        return Err(errors::ErrorCode::GameNotClosable.into())
+    }
+
+    emit!(GameClosed {
+        identifier: game.identifier,
+    });
+
+    Ok(())
+}
+
 
 #[derive(Accounts)]
-#[instruction(identifier: u64, players: u8)]
-pub struct StartGame<'info> {
+#[instruction(identifier: u64)]
+pub struct CreateGame<'info> {
     #[account(init, seeds = [b"game".as_ref(), identifier.to_string().as_bytes()],
     bump, 
     payer = storage, 
-    space = players as usize*Game::STATS_LEN + Game::MISC_LEN)]
-    pub game: Account<'info, Game>,
+    space = Game::LEN)]
+    pub game: AccountLoader<'info, Game>,
     #[account(mut)]
     pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.oracle == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
 
 
 #[derive(Accounts)]
 #[instruction(identifier: u64, bump: u8)]
-pub struct EndGame<'info> {
+pub struct UpdateGame<'info> { //oracle changes of an existing game, shared by extend_roster, start_game, end_game and cancel_game which each check their own status
     #[account(mut)]
     pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.oracle == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
     #[account(mut, seeds = [b"game".as_ref(), identifier.to_string().as_bytes()], bump = bump)]
-    pub game: Account<'info, Game>,
+    pub game: AccountLoader<'info, Game>,
+}
+
+#[derive(Accounts)]
+#[instruction(identifier: u64, bump: u8)]
+pub struct ArchiveGame<'info> {
+    #[account(init, seeds = [b"archive".as_ref(), identifier.to_string().as_bytes()],
+    bump,
+    payer = storage,
+    space = GameArchive::LEN)]
+    pub archive: Account<'info, GameArchive>,
+    #[account(seeds = [b"game".as_ref(), identifier.to_string().as_bytes()], bump = bump)]
+    pub game: AccountLoader<'info, Game>,
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.oracle == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(identifier: u64, bump: u8)]
+pub struct CloseGame<'info> {
+    #[account(mut, close = storage, seeds = [b"game".as_ref(), identifier.to_string().as_bytes()], bump = bump)]
+    pub game: AccountLoader<'info, Game>,
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.oracle == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
 }
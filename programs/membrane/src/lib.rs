@@ -3,12 +3,19 @@ use maths::*;
 use game_state::*;
 use token_state::*;
 use player_state::*;
+use config_state::*;
+use multisig_state::*;
+use pool_state::*;
 pub mod constants;
 pub mod errors;
+pub mod events;
 pub mod maths;
 pub mod player_state;
 pub mod game_state;
 pub mod token_state;
+pub mod pool_state;
+pub mod config_state;
+pub mod multisig_state;
 
 declare_id!("FUbwV7PHj34RaBkifLAkcQ4zdtK6heSWhHVG6qWz5M1o");
 
@@ -17,6 +24,74 @@ pub mod membrane {
 
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>, max_supply: u64, daily_mint_cap: u64, emission_per_day: u64) -> Result<()> {
+        config_state::initialize_config(ctx, max_supply, daily_mint_cap, emission_per_day)
+    }
+
+    pub fn set_roles(ctx: Context<SetRoles>, oracle: Pubkey, treasurer: Pubkey, param_manager: Pubkey) -> Result<()> {
+        config_state::set_roles(ctx, oracle, treasurer, param_manager)
+    }
+
+    pub fn set_pause(ctx: Context<SetPause>, paused: u8) -> Result<()> { //PAUSE_* flags, 0 resumes everything
+        config_state::set_pause(ctx, paused)
+    }
+
+    pub fn check_solvency(ctx: Context<CheckSolvency>) -> Result<()> { //view, emits SolvencyChecked
+        config_state::check_solvency(ctx)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        config_state::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> { //signed by the proposed admin
+        config_state::accept_admin(ctx)
+    }
+
+    pub fn initialize_multisig(ctx: Context<InitializeMultisig>, owners: Vec<Pubkey>, threshold: u8, large_mint: u64) -> Result<()> {
+        multisig_state::initialize_multisig(ctx, owners, threshold, large_mint)
+    }
+
+    pub fn propose_transaction(ctx: Context<ProposeTransaction>, nonce: u64, action: MultisigAction) -> Result<()> { //signer is an owner
+        multisig_state::propose_transaction(ctx, nonce, action)
+    }
+
+    pub fn approve_transaction(ctx: Context<ApproveTransaction>, nonce: u64) -> Result<()> {
+        multisig_state::approve_transaction(ctx, nonce)
+    }
+
+    pub fn cancel_transaction(ctx: Context<CancelTransaction>, nonce: u64) -> Result<()> { //signed by the proposer
+        multisig_state::cancel_transaction(ctx, nonce)
+    }
+
+    pub fn execute_transaction<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteTransaction<'info>>, nonce: u64) -> Result<()> { //permissionless once approved
+        multisig_state::execute_transaction(ctx, nonce)
+    }
+
+    pub fn initialize_params(ctx: Context<InitializeParams>, economy: Economy) -> Result<()> {
+        config_state::initialize_params(ctx, economy)
+    }
+
+    pub fn propose_params(ctx: Context<ProposeChange>, economy: Economy) -> Result<()> {
+        config_state::propose_params(ctx, economy)
+    }
+
+    pub fn propose_emission(ctx: Context<ProposeChange>, per_day: u64) -> Result<()> {
+        config_state::propose_emission(ctx, per_day)
+    }
+
+    pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> { //parameter manager or admin
+        config_state::cancel_change(ctx)
+    }
+
+    pub fn propose_pool(ctx: Context<ProposeChange>, fee: u64, vault_share: u64) -> Result<()> { //fee and vault share in BPS
+        config_state::propose_pool(ctx, fee, vault_share)
+    }
+
+    pub fn execute_change<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteChange<'info>>) -> Result<()> { //permissionless once the timelock expires
+        config_state::execute_change(ctx)
+    }
+
     pub fn initialize_nft_multiplier(ctx: Context<InitializeMultiplier>) -> Result<()> {
         game_state::initialize_nft_multiplier(ctx)
     }
@@ -25,18 +100,42 @@ pub mod membrane {
         game_state::initialize_reward(ctx)
     }
 
-    pub fn update_nft_multiplier(ctx: Context<UpdateMultiplier>, stats: AvgStats, nfts: NftQualities) -> Result<()> {
-        game_state::update_nft_multiplier(ctx, stats, nfts)
+    pub fn roll_reward_day(ctx: Context<RollRewardDay>) -> Result<()> { //permissionless
+        game_state::roll_reward_day(ctx)
+    }
+
+    pub fn propose_nft_multiplier(ctx: Context<ProposeChange>, stats: AvgStats, nfts: NftQualities) -> Result<()> {
+        config_state::propose_nft_multiplier(ctx, stats, nfts)
+    }
+
+    pub fn create_game(ctx: Context<CreateGame>, identifier: u64, roster: Vec<Pubkey>) -> Result<()> {
+        game_state::create_game(ctx, identifier, roster)
     }
 
-    pub fn start_game(ctx: Context<StartGame>, identifier: u64, players: u8) -> Result<()> {
-        game_state::start_game(ctx, identifier, players)
+    pub fn extend_roster(ctx: Context<UpdateGame>, identifier: u64, bump: u8, roster: Vec<Pubkey>) -> Result<()> {
+        game_state::extend_roster(ctx, identifier, bump, roster)
     }
 
-    pub fn end_game(ctx: Context<EndGame>, identifier: u64, bump: u8) -> Result<()> {
+    pub fn start_game(ctx: Context<UpdateGame>, identifier: u64, bump: u8) -> Result<()> {
+        game_state::start_game(ctx, identifier, bump)
+    }
+
+    pub fn end_game(ctx: Context<UpdateGame>, identifier: u64, bump: u8) -> Result<()> {
         game_state::end_game(ctx, identifier, bump)
     }
 
+    pub fn cancel_game(ctx: Context<UpdateGame>, identifier: u64, bump: u8) -> Result<()> {
+        game_state::cancel_game(ctx, identifier, bump)
+    }
+
+    pub fn archive_game(ctx: Context<ArchiveGame>, identifier: u64, bump: u8) -> Result<()> {
+        game_state::archive_game(ctx, identifier, bump)
+    }
+
+    pub fn close_game(ctx: Context<CloseGame>, identifier: u64, bump: u8) -> Result<()> {
+        game_state::close_game(ctx, identifier, bump)
+    }
+
     pub fn transfer_authority(ctx: Context<TransferAuthority>) -> Result<()> { //transfer authority to mint tokens to PDA
         token_state::transfer_authority(ctx)
     } 
@@ -53,26 +152,54 @@ pub mod membrane {
         token_state::mint_token(ctx, amount)
     }
 
-    pub fn user_sell(ctx: Context<SellAndBurn>, amount: u64) -> Result<()> { //signer is user, authority is the storage
-        token_state::user_sell(ctx, amount)
+    pub fn user_sell(ctx: Context<SellAndBurn>, amount: u64, min_out: u64) -> Result<()> { //signer is user, authority is the storage, min_out in lamports
+        token_state::user_sell(ctx, amount, min_out)
     }
 
-    pub fn calculate_reward(ctx: Context<CalculateReward>, placement: u64, kills: u64, identifier: u64, bump: u8) -> Result<()> {
-        game_state::calculate_reward(ctx, placement, kills, identifier, bump)
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> { //signer is the treasurer, amount in lamports
+        token_state::withdraw_treasury(ctx, amount)
     }
 
-    pub fn user_approve(ctx: Context<UserClaim>) -> Result<()> {
-        game_state::user_claim(ctx)
+    pub fn calculate_reward(ctx: Context<CalculateReward>, placement: u8, kills: u8, quality: NftQuality, identifier: u64, bump: u8) -> Result<()> {
+        game_state::calculate_reward(ctx, placement, kills, quality, identifier, bump)
+    }
+
+    pub fn settle_game<'info>(ctx: Context<'_, '_, '_, 'info, SettleGame<'info>>, identifier: u64, bump: u8, offset: u8, results: Vec<PlayerResult>) -> Result<()> {
+        game_state::settle_game(ctx, identifier, bump, offset, results)
     }
 
-    pub fn user_claim(ctx: Context<UserClaim>) -> Result<()> {
+    pub fn user_claim(ctx: Context<UserClaim>) -> Result<()> { //claims everything
         game_state::user_claim(ctx)
     }
 
+    pub fn claim(ctx: Context<UserClaim>, amount: u64) -> Result<()> {
+        game_state::claim(ctx, amount)
+    }
+
+    pub fn initialize_pool(ctx: Context<InitializePool>, fee: u64, vault_share: u64) -> Result<()> { //fee and vault share in BPS
+        pool_state::initialize_pool(ctx, fee, vault_share)
+    }
+
+    pub fn add_liquidity(ctx: Context<UpdateLiquidity>, plasma_amount: u64, sol_amount: u64, min_lp: u64) -> Result<()> {
+        pool_state::add_liquidity(ctx, plasma_amount, sol_amount, min_lp)
+    }
+
+    pub fn remove_liquidity(ctx: Context<UpdateLiquidity>, lp: u64, min_plasma: u64, min_sol: u64) -> Result<()> {
+        pool_state::remove_liquidity(ctx, lp, min_plasma, min_sol)
+    }
+
+    pub fn swap(ctx: Context<Swap>, direction: SwapDirection, amount_in: u64, min_out: u64) -> Result<()> { //signer is the trader
+        pool_state::swap(ctx, direction, amount_in, min_out)
+    }
+
     pub fn freeze_storage(ctx: Context<FreezeStorage>) -> Result<()> {
         token_state::freeze_storage(ctx)
     }
 
+    pub fn thaw_storage(ctx: Context<FreezeStorage>) -> Result<()> {
+        token_state::thaw_storage(ctx)
+    }
+
     pub fn return_authority(ctx: Context<ReturnAuthority>) -> Result<()> {
         token_state::return_authority(ctx)
     }
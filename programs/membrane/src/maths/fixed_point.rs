@@ -0,0 +1,139 @@
+//Integer fixed-point math for the reward curve
+//Values are u128/i128 scaled by SCALE (1e18), every division truncates toward zero and every shift drops the low bits,
+//so the results are bit-exact with the BigNumber mirror of the backend (tests/utils/maths.ts). Functions return None on overflow
+
+pub const SCALE: u128 = 1_000_000_000_000_000_000; //1.0
+pub const LN_2: u128 = 693_147_180_559_945_309; //ln(2) rounded down
+
+//Natural logarithm of x > 0
+pub fn ln(x: u128) -> Option<i128> {
+    if x == 0 {
+        return None;
+    }
+
+    //Reduce x to m * 2^k with m in [1, 2)
+    let mut k: i128 = 0;
+    let mut m = x;
+    while m >= 2 * SCALE {
+        m >>= 1;
+        k += 1;
+    }
+    while m < SCALE {
+        m <<= 1;
+        k -= 1;
+    }
+
+    //ln(m) = 2 * (s + s^3/3 + s^5/5 + ...) with s = (m - 1)/(m + 1) < 1/3
+    let s = (m - SCALE) * SCALE / (m + SCALE);
+    let s_squared = s * s / SCALE;
+    let mut power = s;
+    let mut sum: u128 = 0;
+    let mut n: u128 = 1;
+    while power > 0 {
+        sum += power / n;
+        power = power * s_squared / SCALE;
+        n += 2;
+    }
+
+    Some(k * LN_2 as i128 + 2 * sum as i128)
+}
+
+//e^y, None if the result does not fit into u128
+pub fn exp(y: i128) -> Option<u128> {
+    if y < 0 {
+        //e^-y = 1/e^y, underflows to 0 for large |y|
+        return Some(match y.checked_neg().and_then(exp) {
+            Some(value) => SCALE * SCALE / value,
+            None => 0,
+        });
+    }
+
+    //Reduce y to k * ln(2) + r with r in [0, ln(2))
+    let y = y as u128;
+    let k = y / LN_2;
+    let r = y - k * LN_2;
+    if k > 64 { //e^r < 2 * SCALE < 2^61, so the shift would overflow
+        return None;
+    }
+
+    //e^r = 1 + r + r^2/2! + r^3/3! + ...
+    let mut term = SCALE;
+    let mut sum = SCALE;
+    let mut n: u128 = 1;
+    while term > 0 {
+        term = term * r / (n * SCALE);
+        sum += term;
+        n += 1;
+    }
+
+    Some(sum << k)
+}
+
+//x^p for x >= 0 and p > 0
+pub fn pow(x: u128, p: u128) -> Option<u128> {
+    if x == 0 {
+        return Some(0);
+    }
+    let exponent = ln(x)?.checked_mul(p as i128)? / SCALE as i128;
+    exp(exponent)
+}
+
+//Logistic function 1/(1 + e^-z), always in [0, SCALE]
+pub fn sigmoid(z: i128) -> u128 {
+    match z.checked_neg().and_then(exp) {
+        Some(value) => SCALE * SCALE / (SCALE + value),
+        None => 0, //e^-z does not fit, the result rounds down to 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E: u128 = 2_718_281_828_459_045_235; //e rounded down
+
+    #[test]
+    fn ln_at_the_boundaries() {
+        assert_eq!(ln(0), None);
+        assert_eq!(ln(1), Some(-41_446_531_673_892_822_292)); //1e-18
+        assert_eq!(ln(SCALE), Some(0));
+        assert_eq!(ln(2 * SCALE), Some(LN_2 as i128));
+        assert_eq!(ln(E), Some(999_999_999_999_999_989));
+        assert_eq!(ln(1_000 * SCALE), Some(6_907_755_278_982_137_029));
+        assert_eq!(ln(20_000 * SCALE), Some(9_903_487_552_536_128_026));
+        assert_eq!(ln(100_000 * SCALE), Some(11_512_925_464_970_228_400));
+        assert_eq!(ln(u128::MAX), Some(47_276_307_437_780_177_258));
+    }
+
+    #[test]
+    fn exp_at_the_boundaries() {
+        assert_eq!(exp(0), Some(SCALE));
+        assert_eq!(exp(LN_2 as i128), Some(2 * SCALE));
+        assert_eq!(exp(SCALE as i128), Some(2_718_281_828_459_045_222));
+        assert_eq!(exp(-(SCALE as i128)), Some(367_879_441_171_442_323));
+        assert!(exp(64 * LN_2 as i128).is_some());
+        assert_eq!(exp(65 * LN_2 as i128), None); //does not fit into u128
+        assert_eq!(exp(-100 * SCALE as i128), Some(0)); //underflows
+    }
+
+    #[test]
+    fn pow_at_the_boundaries() {
+        assert_eq!(pow(0, SCALE), Some(0));
+        assert_eq!(pow(1, SCALE), Some(1));
+        assert_eq!(pow(SCALE, 1_000 * SCALE), Some(SCALE));
+        assert_eq!(pow(2 * SCALE, SCALE), Some(2 * SCALE));
+        assert_eq!(pow(E, SCALE), Some(2_718_281_828_459_045_190));
+        assert_eq!(pow(1_000 * SCALE, SCALE / 2), Some(31_622_776_601_683_792_928));
+        assert_eq!(pow(1_000 * SCALE, 1_000 * SCALE), None);
+    }
+
+    #[test]
+    fn sigmoid_at_the_boundaries() {
+        assert_eq!(sigmoid(0), SCALE / 2);
+        assert_eq!(sigmoid(SCALE as i128), 731_058_578_630_004_878);
+        assert_eq!(sigmoid(-(SCALE as i128)), 268_941_421_369_995_121);
+        assert_eq!(sigmoid(100 * SCALE as i128), SCALE);
+        assert_eq!(sigmoid(-100 * SCALE as i128), 0);
+        assert_eq!(sigmoid(i128::MIN), 0);
+    }
+}
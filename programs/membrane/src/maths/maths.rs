@@ -1,47 +1,203 @@
 pub use anchor_lang::prelude::*;
 pub use crate::constants;
+use crate::config_state::{Config, Economy, Params};
+use crate::errors;
+use super::fixed_point::{self, SCALE};
+
+//Value of the reward curve on the given day scaled by constants::PRECISION, rounded down
+pub fn reward_multiplier(days: i64) -> Option<u64> {
+    let x = (days.max(0) as u128).checked_mul(SCALE)?;
+    let decay = fixed_point::pow(x, constants::CURVE_EXPONENT)?.checked_mul(constants::CURVE_SLOPE)? / SCALE; //0.5*x^(0.4*1.0005)
+    let z = constants::CURVE_OFFSET.checked_sub(i128::try_from(decay).ok()?)?;
+    let multiplier = fixed_point::sigmoid(z) / (SCALE / constants::PRECISION as u128); //1 - 1/(e^z + 1) = 1/(1 + e^-z)
+    u64::try_from(multiplier).ok()
+}
 
 //Method to calculate_reward
 impl Reward {
-    pub fn calculate_reward(&mut self, nft_multiplier: u64) {
-        let unix_now = Clock::get().unwrap().unix_timestamp; //current time
-        let x:f64 = ((unix_now - constants::START)/constants::SEC_IN_DAY) as f64; //calculate number of days passed since the start
-        let denominator = constants::EULER_NUMBER.powf(10.0-0.5*x.powf(0.4*1.0005_f64.powf(1_f64)))+1_f64; //denominator of math formula
-        let multiplier: u64 = ((1_f64 - 1_f64/(denominator)) *10.0_f64.powf(9.0)) as u64; //math formula
-
-        self.victory = ((constants::NFT_PRICE as f64 / nft_multiplier as f64) * multiplier as f64) as u64; //value of reward given for victory
-        self.top_five = ((constants::NFT_PRICE as f64 / (4.0 * nft_multiplier as f64)) * multiplier as f64) as u64; //value of reward given for top 2 - top 5
-        self.top_ten = ((constants::NFT_PRICE as f64 / (10.0 * nft_multiplier as f64)) * multiplier as f64) as u64; //value of reward given for top 6 - top 10
-        self.kill = ((7.0 * constants::NFT_PRICE as f64 / (150.0 * nft_multiplier as f64)) * multiplier as f64) as u64; //value of reward given for kill
+    //Recompute the tables for the current day and store the day index, returns the curve multiplier used
+    pub fn calculate_reward(&mut self, nft_multiplier: &QualityMultiplier, economy: &Economy) -> Result<u64> {
+        let days = economy.current_day()?;
+        let multiplier = reward_multiplier(days).ok_or(errors::ErrorCode::MathOverflow)?; //math formula
+
+        self.common.calculate(multiplier, nft_multiplier.common, economy)?;
+        self.rare.calculate(multiplier, nft_multiplier.rare, economy)?;
+        self.epic.calculate(multiplier, nft_multiplier.epic, economy)?;
+        self.legendary.calculate(multiplier, nft_multiplier.legendary, economy)?;
+        self.days = days;
+
+        Ok(multiplier)
+    }
+
+    //Payouts are made only against the table of the current day
+    pub fn ensure_current(&self, economy: &Economy) -> Result<()> {
+        if self.days != economy.current_day()? {
+            return Err(errors::ErrorCode::RewardTableStale.into())
+        }
+        Ok(())
+    }
+
+    pub fn table(&self, quality: NftQuality) -> &RewardTable {
+        match quality {
+            NftQuality::Common => &self.common,
+            NftQuality::Rare => &self.rare,
+            NftQuality::Epic => &self.epic,
+            NftQuality::Legendary => &self.legendary,
+        }
+    }
+}
+
+impl RewardTable {
+    pub fn calculate(&mut self, multiplier: u64, nft_multiplier: u64, economy: &Economy) -> Result<()> {
+        //nft_price * share * multiplier / (REWARD_SHARE_BASE * nft_multiplier), rounded down
+        let share = |share: u64| -> Result<u64> {
+            (economy.nft_price as u128 * share as u128 * multiplier as u128)
+                .checked_div(constants::REWARD_SHARE_BASE as u128 * nft_multiplier as u128)
+                .and_then(|value| u64::try_from(value).ok())
+                .ok_or_else(|| errors::ErrorCode::MathOverflow.into())
+        };
+
+        self.victory = share(economy.victory)?; //value of reward given for victory
+        self.top_five = share(economy.top_five)?; //value of reward given for top 2 - top 5
+        self.top_ten = share(economy.top_ten)?; //value of reward given for top 6 - top 10
+        self.kill = share(economy.kill)?; //value of reward given for kill
+
+        Ok(())
+    }
+}
+
+impl RewardHistory {
+    //Keep the tables of the day in the slot of the day, overwriting the one REWARD_HISTORY_LEN days back
+    pub fn record(&mut self, reward: &Reward, multiplier: u64) {
+        let slot = reward.days.rem_euclid(constants::REWARD_HISTORY_LEN as i64) as usize;
+        self.snapshots[slot] = RewardSnapshot {
+            days: reward.days,
+            multiplier,
+            common: reward.common.into(),
+            rare: reward.rare.into(),
+            epic: reward.epic.into(),
+            legendary: reward.legendary.into(),
+        };
+    }
+
+    pub const LEN: usize = constants::DISCRIMINATOR + std::mem::size_of::<RewardHistory>();
+}
+
+impl From<RewardTable> for TableSnapshot {
+    fn from(table: RewardTable) -> Self {
+        TableSnapshot {
+            victory: table.victory,
+            top_five: table.top_five,
+            top_ten: table.top_ten,
+            kill: table.kill,
+        }
     }
 }
 
 #[derive(Accounts)]
 pub struct InitializeReward<'info> {
-    #[account(init, payer = payer, space = constants::MAX_SIZE_REWARD)]
+    #[account(init, payer = payer, space = constants::MAX_SIZE_REWARD, seeds = [constants::REWARD_PDA_SEED], bump)]
     pub reward: Account<'info, Reward>,
+    #[account(init, payer = payer, space = RewardHistory::LEN, seeds = [constants::REWARD_HISTORY_PDA_SEED], bump)]
+    pub history: AccountLoader<'info, RewardHistory>,
+    #[account(seeds = [constants::MULTIPLIER_PDA_SEED], bump = nft_multiplier.bump)]
     pub nft_multiplier: Account<'info, QualityMultiplier>,
+    #[account(seeds = [constants::PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(seeds = [constants::CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == payer.key() @ errors::ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Update<'info> {
-    #[account(mut)]
+pub struct RollRewardDay<'info> {
+    #[account(mut, seeds = [constants::REWARD_PDA_SEED], bump = reward.bump)]
     pub reward: Account<'info, Reward>,
+    #[account(mut, seeds = [constants::REWARD_HISTORY_PDA_SEED], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, RewardHistory>,
+    #[account(seeds = [constants::MULTIPLIER_PDA_SEED], bump = nft_multiplier.bump)]
+    pub nft_multiplier: Account<'info, QualityMultiplier>,
+    #[account(seeds = [constants::PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
 }
 
 #[account]
 pub struct Reward {
+    pub common: RewardTable,
+    pub rare: RewardTable,
+    pub epic: RewardTable,
+    pub legendary: RewardTable,
+    pub days: i64,
+    pub bump: u8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct RewardTable {
     pub victory: u64,
     pub top_five: u64,
     pub top_ten: u64,
     pub kill: u64,
+}
+
+#[account(zero_copy)]
+#[repr(C, packed)]
+pub struct RewardHistory { //8 + 8641 with 60 days
+    pub snapshots: [RewardSnapshot; constants::REWARD_HISTORY_LEN], //snapshot of day d is kept at d % REWARD_HISTORY_LEN
+    pub bump: u8,
+}
+
+#[zero_copy]
+#[repr(C, packed)]
+#[derive(Debug, Default)]
+pub struct RewardSnapshot { //tables in effect on the given day
     pub days: i64,
+    pub multiplier: u64, //value of the reward curve scaled by PRECISION
+    pub common: TableSnapshot,
+    pub rare: TableSnapshot,
+    pub epic: TableSnapshot,
+    pub legendary: TableSnapshot,
+}
+
+#[zero_copy]
+#[repr(C, packed)]
+#[derive(Debug, Default)]
+pub struct TableSnapshot {
+    pub victory: u64,
+    pub top_five: u64,
+    pub top_ten: u64,
+    pub kill: u64,
 }
 
 #[account]
 pub struct QualityMultiplier {
-    pub common: u64, //TODO: add other qualities
-}
\ No newline at end of file
+    pub common: u64,
+    pub rare: u64,
+    pub epic: u64,
+    pub legendary: u64,
+    pub bump: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum NftQuality {
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reward_multiplier_matches_the_backend_curve() { //values of tests/maths.ts
+        assert_eq!(reward_multiplier(-1), Some(999_954_602));
+        assert_eq!(reward_multiplier(0), Some(999_954_602));
+        assert_eq!(reward_multiplier(1), Some(999_925_153));
+        assert_eq!(reward_multiplier(1000), Some(887_412_419));
+        assert_eq!(reward_multiplier(20000), Some(81));
+        assert_eq!(reward_multiplier(100000), Some(0));
+    }
+}
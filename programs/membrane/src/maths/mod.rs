@@ -1,3 +1,4 @@
 pub use maths::*;
 
 pub mod maths;
+pub mod fixed_point;
@@ -0,0 +1,4 @@
+pub use multisig_state::*;
+pub use multisig_instructions::*;
+pub mod multisig_state;
+pub mod multisig_instructions;
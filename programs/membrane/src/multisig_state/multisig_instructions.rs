@@ -0,0 +1,181 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use super::{ApproveTransaction, CancelTransaction, ExecuteTransaction, InitializeMultisig, Multisig, MultisigAction, ProposeTransaction};
+use crate::events::{AdminProposed, MultisigInitialized, MultisigUpdated, RolesUpdated, TransactionApproved, TransactionCancelled, TransactionExecuted, TransactionProposed};
+use crate::errors;
+use crate::token_state;
+
+//Fn to turn on the multisig, from now on the admin alone cannot reach the treasury
+pub fn initialize_multisig(ctx: Context<InitializeMultisig>, owners: Vec<Pubkey>, threshold: u8, large_mint: u64) -> Result<()> {
+    Multisig::validate(&owners, threshold)?;
+
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.owners = owners;
+    multisig.threshold = threshold;
+    multisig.nonce = 0;
+    multisig.owner_set = 0;
+    multisig.bump = *ctx.bumps.get("multisig").unwrap();
+
+    let config = &mut ctx.accounts.config;
+    config.multisig_enabled = true;
+    config.large_mint = large_mint;
+
+    emit!(MultisigInitialized {
+        owners: multisig.owners.clone(),
+        threshold,
+        large_mint,
+    });
+
+    Ok(())
+}
+
+//Fn for an owner to propose an action, the proposal counts as its approval
+pub fn propose_transaction(ctx: Context<ProposeTransaction>, nonce: u64, action: MultisigAction) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    let index = multisig.owner_index(ctx.accounts.owner.key)?;
+    multisig.nonce += 1;
+
+    let transaction = &mut ctx.accounts.transaction;
+    transaction.nonce = nonce;
+    transaction.proposer = ctx.accounts.owner.key();
+    transaction.owner_set = multisig.owner_set;
+    transaction.action = action;
+    transaction.approvals = vec![false; multisig.owners.len()];
+    transaction.approvals[index] = true;
+    transaction.bump = *ctx.bumps.get("transaction").unwrap();
+
+    emit!(TransactionProposed {
+        nonce,
+        proposer: transaction.proposer,
+        action,
+    });
+
+    Ok(())
+}
+
+pub fn approve_transaction(ctx: Context<ApproveTransaction>, nonce: u64) -> Result<()> {
+    let index = ctx.accounts.multisig.owner_index(ctx.accounts.owner.key)?;
+
+    let transaction = &mut ctx.accounts.transaction;
+    if transaction.approvals[index] {
+        return Err(errors::ErrorCode::AlreadyApproved.into())
+    }
+    transaction.approvals[index] = true;
+
+    emit!(TransactionApproved {
+        nonce,
+        owner: ctx.accounts.owner.key(),
+        approvals: transaction.approval_count(),
+    });
+
+    Ok(())
+}
+
+pub fn cancel_transaction(_ctx: Context<CancelTransaction>, nonce: u64) -> Result<()> {
+    emit!(TransactionCancelled { nonce });
+
+    Ok(())
+}
+
+//Fn to run an action once it has the threshold of approvals, anyone can call it
+pub fn execute_transaction<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteTransaction<'info>>, nonce: u64) -> Result<()> {
+    let action = ctx.accounts.transaction.action;
+    if ctx.accounts.transaction.approval_count() < ctx.accounts.multisig.threshold {
+        return Err(errors::ErrorCode::NotEnoughApprovals.into())
+    }
+
+    match action {
+        MultisigAction::ReturnAuthority { to } => {
+            ensure_pda_authority(&ctx.accounts)?;
+            token_state::hand_back_authority(
+                &ctx.accounts.config,
+                &ctx.accounts.mint,
+                ctx.accounts.vault_token.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                target(ctx.remaining_accounts, to)?,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            )?;
+        },
+        MultisigAction::MintToken { to, amount } => {
+            ensure_pda_authority(&ctx.accounts)?;
+            let day = ctx.accounts.params.economy.current_day()?;
+            token_state::mint_with_pda(
+                &mut ctx.accounts.config,
+                &ctx.accounts.mint,
+                target(ctx.remaining_accounts, to)?,
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                day,
+                amount,
+            )?;
+        },
+        MultisigAction::WithdrawTreasury { to, amount } => {
+            token_state::withdraw_from_treasury(
+                &ctx.accounts.config,
+                ctx.accounts.treasury.to_account_info(),
+                target(ctx.remaining_accounts, to)?,
+                ctx.accounts.system_program.to_account_info(),
+                *ctx.bumps.get("treasury").unwrap(),
+                amount,
+            )?;
+        },
+        MultisigAction::ProposeAdmin { new_admin } => {
+            let config = &mut ctx.accounts.config;
+            config.pending_admin = Some(new_admin);
+            emit!(AdminProposed {
+                admin: config.admin,
+                pending_admin: new_admin,
+            });
+        },
+        MultisigAction::SetRoles { oracle, treasurer, param_manager } => {
+            let config = &mut ctx.accounts.config;
+            config.oracle = oracle;
+            config.treasurer = treasurer;
+            config.param_manager = param_manager;
+            emit!(RolesUpdated { oracle, treasurer, param_manager });
+        },
+        MultisigAction::AddOwner { owner, threshold } => {
+            let multisig = &mut ctx.accounts.multisig;
+            let mut owners = multisig.owners.clone();
+            owners.push(owner); //validate rejects a duplicate and more than MAX_MULTISIG_OWNERS
+            multisig.set_owners(owners, threshold)?;
+            emit!(MultisigUpdated { owners: multisig.owners.clone(), threshold });
+        },
+        MultisigAction::RemoveOwner { owner, threshold } => {
+            let multisig = &mut ctx.accounts.multisig;
+            let index = multisig.owner_index(&owner)?;
+            let mut owners = multisig.owners.clone();
+            owners.remove(index);
+            multisig.set_owners(owners, threshold)?;
+            emit!(MultisigUpdated { owners: multisig.owners.clone(), threshold });
+        },
+        MultisigAction::ChangeThreshold { threshold } => {
+            let multisig = &mut ctx.accounts.multisig;
+            let owners = multisig.owners.clone();
+            multisig.set_owners(owners, threshold)?;
+            emit!(MultisigUpdated { owners: multisig.owners.clone(), threshold });
+        },
+    };
+
+    emit!(TransactionExecuted { nonce, action });
+
+    Ok(())
+}
+
+//Only the token actions need the authorities, the config and owner actions keep working once they are returned
+fn ensure_pda_authority(accounts: &ExecuteTransaction) -> Result<()> {
+    let authority = accounts.authority.key();
+    if accounts.mint.mint_authority != COption::Some(authority) || accounts.vault_token.owner != authority {
+        return Err(errors::ErrorCode::AuthorityNotTransferred.into())
+    }
+    Ok(())
+}
+
+//Account named by a token or treasury action, passed as the first of the remaining accounts
+fn target<'info>(remaining_accounts: &[AccountInfo<'info>], key: Pubkey) -> Result<AccountInfo<'info>> {
+    match remaining_accounts.first() {
+        Some(account) if account.key() == key => Ok(account.clone()),
+        _ => Err(errors::ErrorCode::InvalidTarget.into()),
+    }
+}
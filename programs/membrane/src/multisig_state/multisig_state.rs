@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::constants::*;
+use crate::config_state::{Config, Params};
+use crate::errors;
+
+#[derive(Accounts)]
+pub struct InitializeMultisig<'info> {
+    #[account(init, payer = storage, space = MAX_MULTISIG_SIZE, seeds = [MULTISIG_PDA_SEED], bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == storage.key() @ errors::ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct ProposeTransaction<'info> {
+    #[account(init, seeds = [MULTISIG_TX_PDA_SEED, nonce.to_string().as_bytes()],
+    bump,
+    payer = owner,
+    space = MAX_MULTISIG_TX_SIZE)]
+    pub transaction: Account<'info, MultisigTransaction>,
+    #[account(mut, seeds = [MULTISIG_PDA_SEED], bump = multisig.bump, constraint = multisig.nonce == nonce @ errors::ErrorCode::InvalidMultisig)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct ApproveTransaction<'info> {
+    #[account(mut, seeds = [MULTISIG_TX_PDA_SEED, nonce.to_string().as_bytes()], bump = transaction.bump,
+        constraint = transaction.owner_set == multisig.owner_set @ errors::ErrorCode::StaleTransaction)]
+    pub transaction: Account<'info, MultisigTransaction>,
+    #[account(seeds = [MULTISIG_PDA_SEED], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CancelTransaction<'info> {
+    #[account(mut, close = proposer, seeds = [MULTISIG_TX_PDA_SEED, nonce.to_string().as_bytes()], bump = transaction.bump,
+        constraint = transaction.proposer == proposer.key() @ errors::ErrorCode::NotMultisigOwner)]
+    pub transaction: Account<'info, MultisigTransaction>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct ExecuteTransaction<'info> { //token and treasury actions pass the account named by the action through remaining_accounts
+    #[account(mut, close = proposer, seeds = [MULTISIG_TX_PDA_SEED, nonce.to_string().as_bytes()], bump = transaction.bump,
+        constraint = transaction.owner_set == multisig.owner_set @ errors::ErrorCode::StaleTransaction)]
+    pub transaction: Account<'info, MultisigTransaction>,
+    /// CHECK: SAFE, receives the rent of the transaction
+    #[account(mut, address = transaction.proposer @ errors::ErrorCode::NotMultisigOwner)]
+    pub proposer: AccountInfo<'info>,
+    #[account(mut, seeds = [MULTISIG_PDA_SEED], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
+    /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(mut, seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
+    pub authority: AccountInfo<'info>, //PDA, has to hold the mint and vault authorities for MintToken and ReturnAuthority
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
+    pub vault_token: Account<'info, TokenAccount>,
+    /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(mut, seeds = [TREASURY_PDA_SEED], bump)]
+    pub treasury: AccountInfo<'info>, //PDA, pays WithdrawTreasury
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct Multisig { //singleton, once initialized the treasury-critical instructions need its approval
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8, //approvals required to execute a transaction
+    pub nonce: u64, //seed of the next transaction
+    pub owner_set: u64, //bumped whenever the owners change, transactions of an earlier set cannot be approved or executed
+    pub bump: u8,
+}
+
+impl Multisig {
+    pub fn validate(owners: &[Pubkey], threshold: u8) -> Result<()> {
+        let unique = owners.iter().enumerate().all(|(i, owner)| !owners[..i].contains(owner));
+        let valid = !owners.is_empty()
+            && owners.len() <= MAX_MULTISIG_OWNERS
+            && unique
+            && (1..=owners.len()).contains(&(threshold as usize));
+        if !valid {
+            return Err(errors::ErrorCode::InvalidMultisig.into())
+        }
+        Ok(())
+    }
+
+    //Replace the owners and the threshold, approvals of the pending transactions only count for the set they were proposed to
+    pub fn set_owners(&mut self, owners: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        Multisig::validate(&owners, threshold)?;
+        if owners != self.owners {
+            self.owner_set += 1;
+        }
+        self.owners = owners;
+        self.threshold = threshold;
+        Ok(())
+    }
+
+    pub fn owner_index(&self, key: &Pubkey) -> Result<usize> {
+        match self.owners.iter().position(|owner| owner == key) {
+            Some(index) => Ok(index),
+            None => Err(errors::ErrorCode::NotMultisigOwner.into()),
+        }
+    }
+}
+
+#[account]
+pub struct MultisigTransaction { //action waiting for the approvals of the owners
+    pub nonce: u64,
+    pub proposer: Pubkey, //owner that pays and gets back the rent
+    pub owner_set: u64, //Multisig.owner_set the approvals are indexed by
+    pub action: MultisigAction,
+    pub approvals: Vec<bool>, //indexed by the position in Multisig.owners
+    pub bump: u8,
+}
+
+impl MultisigTransaction {
+    pub fn approval_count(&self) -> u8 {
+        self.approvals.iter().filter(|approved| **approved).count() as u8
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum MultisigAction {
+    ReturnAuthority { to: Pubkey }, //mint, freeze and vault authorities and the PDA lamports
+    MintToken { to: Pubkey, amount: u64 }, //token account
+    WithdrawTreasury { to: Pubkey, amount: u64 }, //lamports of the treasury PDA
+    ProposeAdmin { new_admin: Pubkey }, //still has to accept_admin
+    SetRoles { oracle: Pubkey, treasurer: Pubkey, param_manager: Pubkey },
+    AddOwner { owner: Pubkey, threshold: u8 },
+    RemoveOwner { owner: Pubkey, threshold: u8 },
+    ChangeThreshold { threshold: u8 },
+}
@@ -1,12 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{program::invoke, system_instruction};
 use crate::constants;
-use crate::constants::FEE_LAMPORTS;
+use crate::config_state::{Config, Params};
+use crate::errors;
+use crate::events::{PlayerInitialized, PlayerUpdated};
 
 pub fn create_player(ctx: Context<InitializePlayer>, rating: Option<i64>) -> Result<()> {
+    let fee_lamports = ctx.accounts.params.economy.fee_lamports;
     
     invoke(
-        &system_instruction::transfer(ctx.accounts.user.key, ctx.accounts.storage.key, FEE_LAMPORTS),
+        &system_instruction::transfer(ctx.accounts.user.key, ctx.accounts.storage.key, fee_lamports),
         &[
             ctx.accounts.user.to_account_info(),
             ctx.accounts.storage.to_account_info(),
@@ -25,6 +28,12 @@ pub fn create_player(ctx: Context<InitializePlayer>, rating: Option<i64>) -> Res
         Some(x) => player.rating = Some(x),
         None => player.rating = Some(0),
     };
+
+    emit!(PlayerInitialized {
+        player: player.identity,
+        rating: player.rating.unwrap(),
+        fee: fee_lamports,
+    });
     
     Ok(())
 }
@@ -32,6 +41,11 @@ pub fn create_player(ctx: Context<InitializePlayer>, rating: Option<i64>) -> Res
 pub fn update_player(ctx: Context<UpdatePlayer>, _bump: u8) -> Result<()> {
     let player = &mut ctx.accounts.player;
     player.nft_counter = player.nft_counter + 1; //updated when user buys another nft
+
+    emit!(PlayerUpdated {
+        player: player.identity,
+        nft_counter: player.nft_counter,
+    });
     Ok(())
 }
 
@@ -43,10 +57,14 @@ pub struct InitializePlayer<'info> {
     #[account(mut)]
     pub authority: AccountInfo<'info>, //PDA
     /// CHECK: SAFE OWNED BY TEAM
-    #[account(mut)]
-    pub storage: AccountInfo<'info>,
+    #[account(mut, address = config.admin @ errors::ErrorCode::Unauthorized)]
+    pub storage: AccountInfo<'info>, //receives the registration fee
     #[account(mut)]
     pub user: Signer<'info>,
+    #[account(seeds = [constants::PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
+    #[account(seeds = [constants::CONFIG_PDA_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
 
@@ -0,0 +1,4 @@
+pub use pool_state::*;
+pub use pool_instructions::*;
+pub mod pool_state;
+pub mod pool_instructions;
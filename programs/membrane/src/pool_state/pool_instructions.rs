@@ -0,0 +1,204 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, MintTo, Transfer};
+use super::{InitializePool, Pool, Swap, SwapDirection, UpdateLiquidity, isqrt, mul_div, swap_output};
+use crate::constants::*;
+use crate::events::{LiquidityAdded, LiquidityRemoved, PoolInitialized, Swapped};
+use crate::errors;
+
+pub fn initialize_pool(ctx: Context<InitializePool>, fee: u64, vault_share: u64) -> Result<()> {
+    Pool::validate(fee, vault_share)?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.plasma_reserve = ctx.accounts.plasma_reserve.key();
+    pool.sol_reserve = ctx.accounts.sol_reserve.key();
+    pool.lp_mint = ctx.accounts.lp_mint.key();
+    pool.fee = fee;
+    pool.vault_share = vault_share;
+    pool.bump = *ctx.bumps.get("pool").unwrap();
+
+    emit!(PoolInitialized {
+        lp_mint: pool.lp_mint,
+        fee,
+        vault_share,
+    });
+
+    Ok(())
+}
+
+//Fn to deposit both sides, the first deposit sets the price and locks MINIMUM_LIQUIDITY
+//The locked liquidity keeps a share of both reserves, so a pool every provider left is still priced by its reserves
+pub fn add_liquidity(ctx: Context<UpdateLiquidity>, plasma_amount: u64, sol_amount: u64, min_lp: u64) -> Result<()> {
+    ctx.accounts.config.ensure_unpaused(PAUSE_POOL)?;
+
+    let plasma_reserve = ctx.accounts.plasma_reserve.amount;
+    let sol_reserve = ctx.accounts.sol_reserve.amount;
+    let supply = ctx.accounts.lp_mint.supply;
+
+    let (plasma, sol, lp) = if plasma_reserve == 0 || sol_reserve == 0 { //never seeded, a provided pool keeps both sides
+        let liquidity = isqrt(plasma_amount as u128 * sol_amount as u128) as u64;
+        if liquidity <= MINIMUM_LIQUIDITY {
+            return Err(errors::ErrorCode::ZeroLiquidity.into())
+        }
+        (plasma_amount, sol_amount, liquidity - MINIMUM_LIQUIDITY)
+    } else {
+        let total = supply + MINIMUM_LIQUIDITY; //the locked part is never minted, and owns what is left once supply is 0
+        let lp = mul_div(plasma_amount, total, plasma_reserve, false)?
+            .min(mul_div(sol_amount, total, sol_reserve, false)?);
+        if lp == 0 {
+            return Err(errors::ErrorCode::ZeroLiquidity.into())
+        }
+        //deposit at the pool ratio, rounded in favour of the pool
+        (mul_div(lp, plasma_reserve, total, true)?, mul_div(lp, sol_reserve, total, true)?, lp)
+    };
+    if lp < min_lp {
+        return Err(errors::ErrorCode::SlippageExceeded.into())
+    }
+
+    let accounts = &ctx.accounts;
+    let token_program = accounts.token_program.to_account_info();
+    let provider = accounts.provider.to_account_info();
+    token::transfer(CpiContext::new(token_program.clone(), Transfer {
+        from: accounts.provider_plasma.to_account_info(),
+        to: accounts.plasma_reserve.to_account_info(),
+        authority: provider.clone(),
+    }), plasma)?;
+    token::transfer(CpiContext::new(token_program.clone(), Transfer {
+        from: accounts.provider_sol.to_account_info(),
+        to: accounts.sol_reserve.to_account_info(),
+        authority: provider,
+    }), sol)?;
+
+    let pool_seeds = &[&POOL_PDA_SEED[..], &[accounts.pool.bump]];
+    let seeds = &[&pool_seeds[..]];
+    token::mint_to(CpiContext::new_with_signer(token_program, MintTo {
+        mint: accounts.lp_mint.to_account_info(),
+        to: accounts.provider_lp.to_account_info(),
+        authority: accounts.pool.to_account_info(),
+    }, seeds), lp)?;
+
+    emit!(LiquidityAdded {
+        provider: accounts.provider.key(),
+        plasma,
+        sol,
+        lp,
+    });
+
+    Ok(())
+}
+
+//Fn to burn LP tokens for the share of both reserves, stays open while the pool is paused
+pub fn remove_liquidity(ctx: Context<UpdateLiquidity>, lp: u64, min_plasma: u64, min_sol: u64) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let supply = accounts.lp_mint.supply;
+    if lp == 0 || lp > supply {
+        return Err(errors::ErrorCode::ZeroLiquidity.into())
+    }
+    let total = supply + MINIMUM_LIQUIDITY;
+    let plasma = mul_div(lp, accounts.plasma_reserve.amount, total, false)?;
+    let sol = mul_div(lp, accounts.sol_reserve.amount, total, false)?;
+    if plasma < min_plasma || sol < min_sol {
+        return Err(errors::ErrorCode::SlippageExceeded.into())
+    }
+
+    let token_program = accounts.token_program.to_account_info();
+    token::burn(CpiContext::new(token_program.clone(), Burn {
+        mint: accounts.lp_mint.to_account_info(),
+        from: accounts.provider_lp.to_account_info(),
+        authority: accounts.provider.to_account_info(),
+    }), lp)?;
+
+    let pool_seeds = &[&POOL_PDA_SEED[..], &[accounts.pool.bump]];
+    let seeds = &[&pool_seeds[..]];
+    token::transfer(CpiContext::new_with_signer(token_program.clone(), Transfer {
+        from: accounts.plasma_reserve.to_account_info(),
+        to: accounts.provider_plasma.to_account_info(),
+        authority: accounts.pool.to_account_info(),
+    }, seeds), plasma)?;
+    token::transfer(CpiContext::new_with_signer(token_program, Transfer {
+        from: accounts.sol_reserve.to_account_info(),
+        to: accounts.provider_sol.to_account_info(),
+        authority: accounts.pool.to_account_info(),
+    }, seeds), sol)?;
+
+    emit!(LiquidityRemoved {
+        provider: accounts.provider.key(),
+        plasma,
+        sol,
+        lp,
+    });
+
+    Ok(())
+}
+
+//Fn to trade against the pool, the fee is taken in PLASMA on either side of the trade
+pub fn swap(ctx: Context<Swap>, direction: SwapDirection, amount_in: u64, min_out: u64) -> Result<()> {
+    ctx.accounts.config.ensure_unpaused(PAUSE_POOL)?;
+
+    let accounts = &ctx.accounts;
+    let pool = &accounts.pool;
+    let plasma_reserve = accounts.plasma_reserve.amount;
+    let sol_reserve = accounts.sol_reserve.amount;
+
+    let (amount_out, fee) = match direction {
+        SwapDirection::PlasmaToSol => {
+            let fee = pool.fee_on(amount_in);
+            (swap_output(amount_in - fee, plasma_reserve, sol_reserve)?, fee)
+        },
+        SwapDirection::SolToPlasma => {
+            let gross = swap_output(amount_in, sol_reserve, plasma_reserve)?;
+            let fee = pool.fee_on(gross);
+            (gross - fee, fee)
+        },
+    };
+    if amount_out == 0 || amount_out < min_out {
+        return Err(errors::ErrorCode::SlippageExceeded.into())
+    }
+
+    let (pay_from, pay_to, receive_from, receive_to) = match direction {
+        SwapDirection::PlasmaToSol => (&accounts.trader_plasma, &accounts.plasma_reserve, &accounts.sol_reserve, &accounts.trader_sol),
+        SwapDirection::SolToPlasma => (&accounts.trader_sol, &accounts.sol_reserve, &accounts.plasma_reserve, &accounts.trader_plasma),
+    };
+
+    let token_program = accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(token_program.clone(), Transfer {
+        from: pay_from.to_account_info(),
+        to: pay_to.to_account_info(),
+        authority: accounts.trader.to_account_info(),
+    }), amount_in)?;
+
+    let pool_seeds = &[&POOL_PDA_SEED[..], &[pool.bump]];
+    let seeds = &[&pool_seeds[..]];
+    token::transfer(CpiContext::new_with_signer(token_program.clone(), Transfer {
+        from: receive_from.to_account_info(),
+        to: receive_to.to_account_info(),
+        authority: pool.to_account_info(),
+    }, seeds), amount_out)?;
+
+    //Route the fee out of the PLASMA reserve
+    let (fee_to_vault, fee_burned) = pool.split_fee(fee);
+    if fee_to_vault > 0 {
+        token::transfer(CpiContext::new_with_signer(token_program.clone(), Transfer {
+            from: accounts.plasma_reserve.to_account_info(),
+            to: accounts.vault_token.to_account_info(),
+            authority: pool.to_account_info(),
+        }, seeds), fee_to_vault)?;
+    }
+    if fee_burned > 0 {
+        token::burn(CpiContext::new_with_signer(token_program, Burn {
+            mint: accounts.mint.to_account_info(),
+            from: accounts.plasma_reserve.to_account_info(),
+            authority: pool.to_account_info(),
+        }, seeds), fee_burned)?;
+    }
+
+    emit!(Swapped {
+        trader: accounts.trader.key(),
+        direction,
+        amount_in,
+        amount_out,
+        fee_to_vault,
+        fee_burned,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, Mint, TokenAccount};
+use crate::constants::*;
+use crate::config_state::Config;
+use crate::errors;
+use crate::token_state::spl_token;
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(init, payer = storage, space = MAX_POOL_SIZE, seeds = [POOL_PDA_SEED], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(init, payer = storage, seeds = [POOL_PLASMA_PDA_SEED], bump, token::mint = mint, token::authority = pool)]
+    pub plasma_reserve: Account<'info, TokenAccount>,
+    #[account(init, payer = storage, seeds = [POOL_SOL_PDA_SEED], bump, token::mint = sol_mint, token::authority = pool)]
+    pub sol_reserve: Account<'info, TokenAccount>,
+    #[account(init, payer = storage, seeds = [POOL_LP_PDA_SEED], bump, mint::decimals = LP_DECIMALS, mint::authority = pool)]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(address = config.mint @ errors::ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    #[account(address = spl_token::native_mint::id() @ errors::ErrorCode::InvalidMint)]
+    pub sol_mint: Account<'info, Mint>, //wrapped SOL
+    #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == storage.key() @ errors::ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLiquidity<'info> { //add_liquidity and remove_liquidity
+    #[account(seeds = [POOL_PDA_SEED], bump = pool.bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut, address = pool.plasma_reserve)]
+    pub plasma_reserve: Account<'info, TokenAccount>,
+    #[account(mut, address = pool.sol_reserve)]
+    pub sol_reserve: Account<'info, TokenAccount>,
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+    pub provider: Signer<'info>,
+    #[account(mut)]
+    pub provider_plasma: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub provider_sol: Account<'info, TokenAccount>,
+    #[account(mut, constraint = provider_lp.mint == lp_mint.key() @ errors::ErrorCode::InvalidMint)]
+    pub provider_lp: Account<'info, TokenAccount>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(seeds = [POOL_PDA_SEED], bump = pool.bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut, address = pool.plasma_reserve)]
+    pub plasma_reserve: Account<'info, TokenAccount>,
+    #[account(mut, address = pool.sol_reserve)]
+    pub sol_reserve: Account<'info, TokenAccount>,
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
+    pub mint: Account<'info, Mint>, //burns the fee
+    #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
+    pub vault_token: Account<'info, TokenAccount>, //receives the vault share of the fee
+    pub trader: Signer<'info>,
+    #[account(mut)]
+    pub trader_plasma: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub trader_sol: Account<'info, TokenAccount>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[account]
+pub struct Pool { //singleton constant-product PLASMA/wrapped SOL pool, the PDA owns the reserves and the LP mint
+    pub plasma_reserve: Pubkey,
+    pub sol_reserve: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee: u64, //swap fee in BPS, always taken in PLASMA
+    pub vault_share: u64, //part of the fee sent to the vault in BPS, the rest is burned
+    pub bump: u8,
+}
+
+impl Pool {
+    pub fn validate(fee: u64, vault_share: u64) -> Result<()> {
+        if fee > MAX_SWAP_FEE || vault_share > BPS {
+            return Err(errors::ErrorCode::InvalidPoolParams.into())
+        }
+        Ok(())
+    }
+
+    pub fn fee_on(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee as u128 / BPS as u128) as u64
+    }
+
+    //Split of the fee between the vault and the burn
+    pub fn split_fee(&self, fee: u64) -> (u64, u64) {
+        let to_vault = (fee as u128 * self.vault_share as u128 / BPS as u128) as u64;
+        (to_vault, fee - to_vault)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum SwapDirection {
+    PlasmaToSol,
+    SolToPlasma,
+}
+
+//Output of the constant-product curve x*y=k for an input already net of fees
+pub fn swap_output(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(errors::ErrorCode::PoolEmpty.into())
+    }
+    let out = reserve_out as u128 * amount_in as u128 / (reserve_in as u128 + amount_in as u128);
+    Ok(out as u64) //below reserve_out
+}
+
+//amount*numerator/denominator, rounded up when ceil is set
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64, ceil: bool) -> Result<u64> {
+    let product = amount as u128 * numerator as u128;
+    let denominator = denominator as u128;
+    let quotient = product / denominator;
+    let result = match ceil && quotient * denominator != product {
+        true => quotient + 1,
+        false => quotient,
+    };
+    u64::try_from(result).map_err(|_| errors::ErrorCode::MathOverflow.into())
+}
+
+//Integer square root rounded down, sizes the first deposit
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value
+    }
+    let mut x = value / 2 + 1; //Newton iteration from above
+    let mut y = (x + value / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
@@ -1,7 +1,10 @@
 use anchor_lang::{prelude::*, solana_program::{program::invoke_signed, system_instruction}};
-use anchor_spl::token::{self, MintTo, Burn, Transfer, SetAuthority, FreezeAccount};
-use super::{MintToken, SellAndBurn, TransferAuthority, FreezeStorage, ReturnAuthority};
+use anchor_spl::token::{self, Mint, MintTo, Burn, Transfer, SetAuthority, FreezeAccount, ThawAccount};
+use super::{MintToken, SellAndBurn, TransferAuthority, FreezeStorage, ReturnAuthority, WithdrawTreasury};
+use crate::config_state::Config;
 use crate::constants::*;
+use crate::errors;
+use crate::events::{AuthorityReturned, AuthorityTransferred, StorageFrozen, StorageThawed, TokensMinted, TokensSold, TreasuryWithdrawn, VaultReplenished};
 pub use spl_token;
 
 
@@ -34,38 +37,131 @@ pub fn transfer_authority(ctx: Context<TransferAuthority>) -> Result<()> {
     
     token::set_authority(cpi_ctx, spl_token::instruction::AuthorityType::FreezeAccount, Some(pda_authority))?;
 
+    emit!(AuthorityTransferred {
+        mint: ctx.accounts.mint.key(),
+        vault: ctx.accounts.storage_token_account.key(),
+        authority: pda_authority,
+    });
+
     Ok(())
 }
 
 //Fn to mint tokens to ATA
 pub fn mint_token(ctx: Context<MintToken>, amount: u64) -> Result<()> {
+    let config = &ctx.accounts.config;
+    config.ensure_unpaused(PAUSE_MINTING)?;
+    let day = ctx.accounts.params.economy.current_day()?;
+    if config.multisig_enabled && config.minted_on(day).saturating_add(amount) > config.large_mint { //large_mint bounds the daily total, the rest goes through execute_transaction
+        return Err(errors::ErrorCode::MultisigRequired.into())
+    }
+
+    mint_with_pda(
+        &mut ctx.accounts.config,
+        &ctx.accounts.mint,
+        ctx.accounts.token_account.to_account_info(),
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        day,
+        amount,
+    )
+}
 
-    let (_vault_authority, vault_authority_bump) = Pubkey::find_program_address(&[VAULT_PDA_SEED], ctx.program_id);
-    let authority_seeds = &[&VAULT_PDA_SEED[..], &[vault_authority_bump]];
+//Mint signed by the vault PDA, shared by mint_token and the multisig
+pub fn mint_with_pda<'info>(
+    config: &mut Account<'info, Config>,
+    mint: &Account<'info, Mint>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    day: i64,
+    amount: u64,
+) -> Result<()> {
+    config.ensure_unpaused(PAUSE_MINTING)?; //also stops the multisig mints
+    config.record_mint(mint.supply, amount, day)?; //enforce supply cap and daily budget
+
+    let authority_seeds = &[&VAULT_PDA_SEED[..], &[config.vault_bump]];
+    let seeds = &[&authority_seeds[..]];
 
     let cpi_accounts = MintTo {
-        mint: ctx.accounts.mint.to_account_info(),
-        to: ctx.accounts.token_account.to_account_info(),
-        authority: ctx.accounts.authority.to_account_info(),
+        mint: mint.to_account_info(),
+        to: to.clone(),
+        authority,
     };
-    let cpi_program = ctx
-    .accounts
-    .token_program
-    .to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, seeds);
+
+    token::mint_to(cpi_ctx, amount)?;
 
+    emit!(TokensMinted {
+        to: to.key(),
+        amount,
+        total_minted: config.total_minted,
+        minted_today: config.minted_today,
+    });
+    Ok(())
+}
+
+//Mint up to the shortfall of the vault within the emission schedule and the daily budget, returns the minted amount
+pub fn replenish_vault<'info>(
+    config: &mut Account<'info, Config>,
+    mint: &Account<'info, Mint>,
+    vault_token: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    day: i64,
+    shortfall: u64,
+) -> Result<u64> {
+    if shortfall == 0 || config.paused & PAUSE_MINTING != 0 {
+        return Ok(0)
+    }
+    let amount = shortfall
+        .min(config.emission_allowance(day, mint.supply))
+        .min(config.mint_budget(day));
+    if amount == 0 {
+        return Ok(0)
+    }
+    config.record_emission(amount, day);
+
+    let authority_seeds = &[&VAULT_PDA_SEED[..], &[config.vault_bump]];
     let seeds = &[&authority_seeds[..]];
 
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds);
+    let cpi_accounts = MintTo {
+        mint: mint.to_account_info(),
+        to: vault_token,
+        authority,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, seeds);
 
     token::mint_to(cpi_ctx, amount)?;
-    Ok(())
-}
 
-//Fn when user sells tokens back to the storage
-pub fn user_sell(ctx: Context<SellAndBurn>, amount: u64) -> Result<()> { //signer is user, authority is the storage, amount in 10^9
+    emit!(VaultReplenished {
+        amount,
+        emitted: config.emitted,
+        minted_today: config.minted_today,
+        day,
+    });
+    Ok(amount)
+}
 
-    let (_vault_authority, vault_authority_bump) = Pubkey::find_program_address(&[VAULT_PDA_SEED], ctx.program_id);
-    let authority_seeds = &[&VAULT_PDA_SEED[..], &[vault_authority_bump]];
+//Fn when user sells tokens back to the storage, the treasury pays the player in SOL
+pub fn user_sell(ctx: Context<SellAndBurn>, amount: u64, min_out: u64) -> Result<()> { //signer is user, authority is the storage, amount in 10^9
+    ctx.accounts.config.ensure_unpaused(PAUSE_SELLS)?;
+
+    let economy = &ctx.accounts.params.economy;
+    let day = economy.current_day()?;
+    ctx.accounts.config.record_sell(amount, day, economy.daily_sell_limit)?;
+
+    let payout = economy.sell_payout(amount, ctx.accounts.mint.decimals)?;
+    if payout < min_out {
+        return Err(errors::ErrorCode::SlippageExceeded.into())
+    }
+    let treasury = ctx.accounts.treasury.to_account_info();
+    let available = treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0)); //the treasury stays rent exempt
+    if payout > available {
+        return Err(errors::ErrorCode::TreasuryDepleted.into())
+    }
+    let burned = (amount as u128 * economy.sell_burn as u128 / BPS as u128) as u64;
+
+    let authority_seeds = &[&VAULT_PDA_SEED[..], &[ctx.accounts.config.vault_bump]];
     let seeds = &[&authority_seeds[..]];
 
     //Define Transfer account
@@ -122,12 +218,80 @@ pub fn user_sell(ctx: Context<SellAndBurn>, amount: u64) -> Result<()> { //signe
 
     //Define CpiContext<Burn>
     let cpi_burn_ctx = CpiContext::new_with_signer(cpi_burn_program, cpi_burn_accounts, seeds);
-    token::burn(cpi_burn_ctx, amount/2)?;
+    token::burn(cpi_burn_ctx, burned)?;
 
+    //Pay the player from the treasury PDA
+    let treasury_seeds = &[&TREASURY_PDA_SEED[..], &[*ctx.bumps.get("treasury").unwrap()]];
+    invoke_signed(
+        &system_instruction::transfer(treasury.key, ctx.accounts.player.key, payout),
+        &[
+            treasury,
+            ctx.accounts.player.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[&treasury_seeds[..]],
+    )?;
+
+    emit!(TokensSold {
+        player: ctx.accounts.player.key(),
+        amount,
+        burned,
+        payout,
+    });
+
+    Ok(())
+}
+
+//Fn for the treasurer to take SOL out of the treasury, such as the treasury share kept from the sells
+pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    withdraw_from_treasury(
+        &ctx.accounts.config,
+        ctx.accounts.treasury.to_account_info(),
+        ctx.accounts.to.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        *ctx.bumps.get("treasury").unwrap(),
+        amount,
+    )
+}
+
+//Transfer signed by the treasury PDA, shared by withdraw_treasury and the multisig
+pub fn withdraw_from_treasury<'info>(
+    config: &Account<'info, Config>,
+    treasury: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    treasury_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    config.ensure_unpaused(PAUSE_TREASURY)?;
+    let available = treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0)); //the treasury stays rent exempt
+    if amount > available {
+        return Err(errors::ErrorCode::TreasuryDepleted.into())
+    }
+
+    let treasury_seeds = &[&TREASURY_PDA_SEED[..], &[treasury_bump]];
+    invoke_signed(
+        &system_instruction::transfer(treasury.key, to.key, amount),
+        &[
+            treasury.clone(),
+            to.clone(),
+            system_program,
+        ],
+        &[&treasury_seeds[..]],
+    )?;
+
+    emit!(TreasuryWithdrawn {
+        to: to.key(),
+        amount,
+        balance: treasury.lamports(),
+    });
     Ok(())
 }
 
+//Fn to freeze the vault with the freeze authority held by the PDA
 pub fn freeze_storage(ctx: Context<FreezeStorage>) -> Result<()> {
+    let authority_seeds = &[&VAULT_PDA_SEED[..], &[ctx.accounts.config.vault_bump]];
+    let seeds = &[&authority_seeds[..]];
 
     let cpi_accounts = FreezeAccount {
         account: ctx
@@ -142,7 +306,7 @@ pub fn freeze_storage(ctx: Context<FreezeStorage>) -> Result<()> {
 
         authority: ctx
         .accounts
-        .storage
+        .authority
         .to_account_info(),
     };
 
@@ -151,57 +315,124 @@ pub fn freeze_storage(ctx: Context<FreezeStorage>) -> Result<()> {
     .token_program
     .to_account_info();
 
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds);
     token::freeze_account(cpi_ctx)?;
 
+    emit!(StorageFrozen {
+        mint: ctx.accounts.mint.key(),
+        account: ctx.accounts.storage_token_account.key(),
+    });
+
+    Ok(())
+}
+
+//Fn to unfreeze the vault, a frozen vault blocks claims, sells and return_authority
+pub fn thaw_storage(ctx: Context<FreezeStorage>) -> Result<()> {
+    let authority_seeds = &[&VAULT_PDA_SEED[..], &[ctx.accounts.config.vault_bump]];
+    let seeds = &[&authority_seeds[..]];
+
+    let cpi_accounts = ThawAccount {
+        account: ctx
+        .accounts
+        .storage_token_account
+        .to_account_info(),
+
+        mint: ctx
+        .accounts
+        .mint
+        .to_account_info(),
+
+        authority: ctx
+        .accounts
+        .authority
+        .to_account_info(),
+    };
+
+    let cpi_program = ctx
+    .accounts
+    .token_program
+    .to_account_info();
+
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds);
+    token::thaw_account(cpi_ctx)?;
+
+    emit!(StorageThawed {
+        mint: ctx.accounts.mint.key(),
+        account: ctx.accounts.storage_token_account.key(),
+    });
+
     Ok(())
 }
 
 pub fn return_authority(ctx: Context<ReturnAuthority>) -> Result<()> {
+    hand_back_authority(
+        &ctx.accounts.config,
+        &ctx.accounts.mint,
+        ctx.accounts.storage_token_account.to_account_info(),
+        ctx.accounts.pda.to_account_info(),
+        ctx.accounts.storage.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )
+}
+
+//Hand the mint, freeze and vault authorities and the PDA lamports to the recipient, shared by return_authority and the multisig
+pub fn hand_back_authority<'info>(
+    config: &Account<'info, Config>,
+    mint: &Account<'info, Mint>,
+    storage_token_account: AccountInfo<'info>,
+    pda: AccountInfo<'info>,
+    recipient: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
     
-    let (_pda_authority, vault_authority_bump) = Pubkey::find_program_address(&[VAULT_PDA_SEED], ctx.program_id);
-    let authority_seeds = &[&VAULT_PDA_SEED[..], &[vault_authority_bump]];
+    let authority_seeds = &[&VAULT_PDA_SEED[..], &[config.vault_bump]];
     let seeds = &[&authority_seeds[..]];
 
     let cpi_accounts = SetAuthority {
-        current_authority: ctx.accounts.pda.to_account_info(),
-        account_or_mint: ctx.accounts.mint.to_account_info(),
+        current_authority: pda.clone(),
+        account_or_mint: mint.to_account_info(),
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds);
+    let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, seeds);
 
-    token::set_authority(cpi_ctx, spl_token::instruction::AuthorityType::MintTokens, Some(ctx.accounts.storage.key.clone()))?;
+    token::set_authority(cpi_ctx, spl_token::instruction::AuthorityType::MintTokens, Some(recipient.key()))?;
 
     let cpi_accounts = SetAuthority {
-        current_authority: ctx.accounts.pda.to_account_info(),
-        account_or_mint: ctx.accounts.storage_token_account.to_account_info(),
+        current_authority: pda.clone(),
+        account_or_mint: storage_token_account.clone(),
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds);
+    let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, seeds);
 
-    token::set_authority(cpi_ctx, spl_token::instruction::AuthorityType::AccountOwner, Some(ctx.accounts.storage.key.clone()))?;
+    token::set_authority(cpi_ctx, spl_token::instruction::AuthorityType::AccountOwner, Some(recipient.key()))?;
 
     let cpi_accounts = SetAuthority {
-        current_authority: ctx.accounts.pda.to_account_info(),
-        account_or_mint: ctx.accounts.mint.to_account_info(),
+        current_authority: pda.clone(),
+        account_or_mint: mint.to_account_info(),
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds);
+    let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, seeds);
    
-    token::set_authority(cpi_ctx, spl_token::instruction::AuthorityType::FreezeAccount, Some(ctx.accounts.storage.key.clone()))?;
+    token::set_authority(cpi_ctx, spl_token::instruction::AuthorityType::FreezeAccount, Some(recipient.key()))?;
 
-    let balance = ctx.accounts.pda.to_account_info().lamports();
+    let balance = pda.lamports();
 
     invoke_signed(
-        &system_instruction::transfer(ctx.accounts.pda.key, ctx.accounts.storage.key, balance),
+        &system_instruction::transfer(pda.key, recipient.key, balance),
         &[
-            ctx.accounts.pda.to_account_info(),
-            ctx.accounts.storage.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
+            pda,
+            recipient.clone(),
+            system_program,
         ],
         seeds,
     )?;
 
+    emit!(AuthorityReturned {
+        mint: mint.key(),
+        vault: storage_token_account.key(),
+        authority: recipient.key(),
+        lamports: balance,
+    });
+
     // let cpi_accounts = CloseAccount {
     //     account: ctx.accounts.pda.to_account_info(),
     //     destination: ctx.accounts.storage.to_account_info(),
@@ -1,17 +1,26 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{Token, Mint, TokenAccount};
+use crate::constants::*;
+use crate::config_state::{Config, Params};
+use crate::errors;
 
 
 #[derive(Accounts)]
 pub struct MintToken<'info> {
-    #[account(mut)]
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
     #[account(mut)]
     pub token_account: Account<'info, TokenAccount>,
     /// CHECK: SAFE PROGRAM OWNED ACCOUNT
-    #[account(mut)]
+    #[account(mut, seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
     pub authority: AccountInfo<'info>,
+    pub storage: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.treasurer == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
 }
 
 // #[derive(Accounts)]
@@ -28,52 +37,87 @@ pub struct MintToken<'info> {
 pub struct SellAndBurn<'info> {
     // /// CHECK: Safe because we don't read or write from the account
     // pub program_signer: AccountInfo<'info>,
-    pub player: Signer<'info>,
     #[account(mut)]
+    pub player: Signer<'info>,
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
     pub mint: Account<'info, Mint>,
     #[account(mut)]
     pub player_token: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
     pub vault_token: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
     /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
     pub authority: AccountInfo<'info>,
+    /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(mut, seeds = [TREASURY_PDA_SEED], bump)]
+    pub treasury: AccountInfo<'info>, //PDA holding the SOL of the buyback
+    #[account(mut, seeds = [CONFIG_PDA_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [PARAMS_PDA_SEED], bump = params.bump)]
+    pub params: Account<'info, Params>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct TransferAuthority<'info> {
+pub struct WithdrawTreasury<'info> {
+    /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(mut, seeds = [TREASURY_PDA_SEED], bump)]
+    pub treasury: AccountInfo<'info>, //PDA holding the SOL of the buyback
+    /// CHECK: SAFE, receives the lamports
     #[account(mut)]
+    pub to: AccountInfo<'info>,
     pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.treasurer == storage.key() @ errors::ErrorCode::MissingRole,
+        constraint = !config.multisig_enabled @ errors::ErrorCode::MultisigRequired)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
     #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
-    #[account(mut)]
+    #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
     pub storage_token_account: Account<'info, TokenAccount>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.admin == storage.key() @ errors::ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
 }
 
 #[derive(Accounts)]
 pub struct FreezeStorage<'info> {
-    #[account(mut)]
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint,
+        constraint = mint.freeze_authority == COption::Some(authority.key()) @ errors::ErrorCode::AuthorityNotTransferred)]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
-    #[account(mut)]
+    #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
     pub storage_token_account: Account<'info, TokenAccount>,
+    /// CHECK: SAFE PROGRAM OWNED ACCOUNT
+    #[account(seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
+    pub authority: AccountInfo<'info>, //PDA holding the freeze authority
     #[account(mut)]
     pub storage: Signer<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.treasurer == storage.key() @ errors::ErrorCode::MissingRole)]
+    pub config: Account<'info, Config>,
 }
 
 #[derive(Accounts)]
 pub struct ReturnAuthority<'info> {
-    ///CHECK: Safe, owned by team
-    #[account(mut)]
-    pub storage: AccountInfo<'info>,
     #[account(mut)]
+    pub storage: Signer<'info>,
+    #[account(mut, address = config.mint @ errors::ErrorCode::InvalidMint)]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
-    #[account(mut)]
+    #[account(mut, address = config.vault @ errors::ErrorCode::InvalidVault)]
     pub storage_token_account: Account<'info, TokenAccount>,
     ///CHECK: SAFE PROGRAM OWNED ACCOUNT
-    #[account(mut)]
+    #[account(mut, seeds = [VAULT_PDA_SEED], bump = config.vault_bump)]
     pub pda: AccountInfo<'info>,
+    #[account(seeds = [CONFIG_PDA_SEED], bump = config.bump, constraint = config.treasurer == storage.key() @ errors::ErrorCode::MissingRole,
+        constraint = !config.multisig_enabled @ errors::ErrorCode::MultisigRequired)]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
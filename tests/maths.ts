@@ -0,0 +1,70 @@
+import { BN } from '@project-serum/anchor';
+import Decimal from 'decimal.js';
+import { expect } from 'chai';
+import {
+  calculateRewardTable,
+  exp,
+  ln,
+  rewardMultiplier,
+  SCALE
+} from './utils/maths';
+import { NFT_GRADE_MULTIPLIERS, PRECISION } from './utils/constants';
+
+// Floating point emission curve the program used before fixed-point math
+const floatRewardMultiplier = (days: number): Decimal => {
+  const exponent = new Decimal(0.4).mul(new Decimal(1.0005));
+  const denominator = Decimal.exp(
+    new Decimal(10).sub(new Decimal(0.5).mul(new Decimal(days).pow(exponent)))
+  ).add(1);
+  return new Decimal(1)
+    .sub(new Decimal(1).div(denominator))
+    .mul(PRECISION)
+    .toDecimalPlaces(0, Decimal.ROUND_DOWN);
+};
+
+describe('Fixed-point maths', () => {
+  it('Computes ln and exp', () => {
+    expect(ln(SCALE).isZero()).to.be.true;
+    expect(ln(SCALE.muln(2)).toString()).to.equal('693147180559945309');
+    expect(exp(new BN(0)).eq(SCALE)).to.be.true;
+    expect(exp(SCALE).toString()).to.equal('2718281828459045222');
+    expect(exp(SCALE.muln(100))).to.be.null;
+  });
+
+  it('Reproduces the emission curve', () => {
+    const curve: [number, number][] = [
+      [0, 999_954_602],
+      [1, 999_925_153],
+      [10, 999_840_527],
+      [100, 998_933_500],
+      [365, 990_973_201],
+      [1000, 887_412_419],
+      [5000, 5_992_953],
+      [20000, 81],
+      [100000, 0]
+    ];
+
+    for (const [days, multiplier] of curve) {
+      expect(rewardMultiplier(days).toNumber()).to.equal(multiplier);
+    }
+  });
+
+  it('Stays within one unit of the floating point curve', () => {
+    for (let days = 0; days <= 2000; days += 7) {
+      const fixed = new Decimal(rewardMultiplier(days).toString());
+      expect(fixed.sub(floatRewardMultiplier(days)).abs().lte(1)).to.be.true;
+    }
+  });
+
+  it('Calculates the reward table', () => {
+    const table = calculateRewardTable(
+      0,
+      new BN(NFT_GRADE_MULTIPLIERS.COMMON)
+    );
+
+    expect(table.victory.toNumber()).to.equal(8_928_166_089);
+    expect(table.topFive.toNumber()).to.equal(2_232_041_522);
+    expect(table.topTen.toNumber()).to.equal(892_816_608);
+    expect(table.kill.toNumber()).to.equal(416_647_750);
+  });
+});
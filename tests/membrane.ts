@@ -12,22 +12,59 @@ import { Membrane } from '../target/types/membrane';
 import {
   adjustSupply,
   findAssociatedTokenAddress,
-  getAirdrop
+  getAirdrop,
+  getComputeUnits,
+  getEvents
 } from './utils/web3';
 import { expect } from 'chai';
+import { isqrt, rewardMultiplier } from './utils/maths';
 import {
   calculateInitialRewardParams,
   calculatePlayerPayout,
+  generateGameResults,
   generateRandomGameResult,
   initializeMint,
   Stat
 } from './utils/mocks';
 import {
+  BPS,
+  CONFIG_PDA_SEED,
+  DAILY_SELL_LIMIT,
   FEE_LAMPORTS,
+  GAME_STATUS,
+  PAUSE,
+  KILL_SHARE,
+  MAX_PLAYERS,
+  MULTIPLIER_PDA_SEED,
+  MULTISIG_PDA_SEED,
+  MULTISIG_TX_PDA_SEED,
   NFT_GRADE_MULTIPLIERS,
+  NFT_PRICE,
+  PARAMS_PDA_SEED,
+  PROPOSAL_PDA_SEED,
+  PLASMA_DAILY_MINT_CAP,
   PLASMA_DECIMALS,
+  PLASMA_EMISSION_PER_DAY,
   PLASMA_INITIAL_SUPPLY,
-  VAULT_PDA_SEED
+  PLASMA_MAX_SUPPLY,
+  POOL_LP_PDA_SEED,
+  POOL_PDA_SEED,
+  POOL_PLASMA_PDA_SEED,
+  POOL_SOL_PDA_SEED,
+  REWARD_HISTORY_LEN,
+  REWARD_HISTORY_PDA_SEED,
+  REWARD_PDA_SEED,
+  SEC_IN_DAY,
+  SELL_BURN,
+  SELL_PRICE,
+  START,
+  TIMELOCK_DELAY,
+  TOP_FIVE_SHARE,
+  TOP_TEN_SHARE,
+  TREASURY_PDA_SEED,
+  TREASURY_SHARE,
+  VAULT_PDA_SEED,
+  VICTORY_SHARE
 } from './utils/constants';
 
 describe('Membrane', () => {
@@ -42,16 +79,49 @@ describe('Membrane', () => {
 
   let storage: Keypair;
   let storagePDA: PublicKey; // storage account PDA
-  let reward: Keypair;
-  let nftMultiplier: Keypair;
+  let configPDA: PublicKey; // global config PDA
+  let paramsPDA: PublicKey; // economy parameters PDA
+  let proposalPDA: PublicKey; // pending parameter change PDA
+  let rewardPDA: PublicKey; // reward table PDA
+  let historyPDA: PublicKey; // reward history PDA
+  let nftMultiplierPDA: PublicKey; // nft multiplier PDA
+  let multisigPDA: PublicKey; // multisig PDA
+  let treasuryPDA: PublicKey; // pays the buyback
+  const multisigOwners = [Keypair.generate(), Keypair.generate()]; // co-owners next to storage
   let mintAddress: PublicKey;
-  let storageTokenAddress: PublicKey;
+  let storageTokenAddress: PublicKey; // vault
+  let treasuryTokenAddress: PublicKey; // holds the initial supply
   let player: PublicKey;
   let playerPDA: PublicKey; // player account PDA
   let playerBump: number;
   let gamePDA: PublicKey;
   let gameBump: number;
   const identifier: anchor.BN = new anchor.BN(0);
+  const players = 15;
+  const decimalsMultiplier = new anchor.BN(10).pow(
+    new anchor.BN(PLASMA_DECIMALS)
+  );
+  const maxSupply = new anchor.BN(PLASMA_MAX_SUPPLY).mul(decimalsMultiplier);
+  const dailyMintCap = new anchor.BN(PLASMA_DAILY_MINT_CAP).mul(
+    decimalsMultiplier
+  );
+  const emissionPerDay = new anchor.BN(PLASMA_EMISSION_PER_DAY).mul(
+    decimalsMultiplier
+  );
+  const economy = {
+    start: new anchor.BN(START),
+    secInDay: new anchor.BN(SEC_IN_DAY),
+    nftPrice: new anchor.BN(NFT_PRICE),
+    victory: new anchor.BN(VICTORY_SHARE),
+    topFive: new anchor.BN(TOP_FIVE_SHARE),
+    topTen: new anchor.BN(TOP_TEN_SHARE),
+    kill: new anchor.BN(KILL_SHARE),
+    feeLamports: new anchor.BN(FEE_LAMPORTS),
+    sellPrice: new anchor.BN(SELL_PRICE),
+    sellBurn: new anchor.BN(SELL_BURN),
+    treasuryShare: new anchor.BN(TREASURY_SHARE),
+    dailySellLimit: new anchor.BN(DAILY_SELL_LIMIT)
+  };
 
   before(async () => {
     // TODO: create first initialize script
@@ -64,7 +134,15 @@ describe('Membrane', () => {
     const mintResult = await initializeMint(anchorProvider.connection, storage);
 
     mintAddress = mintResult.mintAddress;
-    storageTokenAddress = mintResult.associatedTokenAddress;
+    treasuryTokenAddress = mintResult.associatedTokenAddress;
+    // The vault starts empty, the emission schedule tops it up for the rewards
+    storageTokenAddress = await spl.createAccount(
+      anchorProvider.connection,
+      storage,
+      mintAddress,
+      storage.publicKey,
+      Keypair.generate()
+    );
 
     // Get storage account PDA
     const [_storagePDA, _storageBump] =
@@ -75,6 +153,50 @@ describe('Membrane', () => {
 
     storagePDA = _storagePDA;
 
+    // Get global config PDA
+    const [_configPDA, _configBump] =
+      await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from(CONFIG_PDA_SEED)],
+        program.programId
+      );
+
+    configPDA = _configPDA;
+
+    // Get economy parameters PDA
+    const [_paramsPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from(PARAMS_PDA_SEED)],
+      program.programId
+    );
+
+    const [_proposalPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from(PROPOSAL_PDA_SEED)],
+      program.programId
+    );
+
+    paramsPDA = _paramsPDA;
+    proposalPDA = _proposalPDA;
+
+    // Get reward table and nft multiplier PDAs
+    const [_rewardPDA, _rewardBump] =
+      await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from(REWARD_PDA_SEED)],
+        program.programId
+      );
+    const [_nftMultiplierPDA, _nftMultiplierBump] =
+      await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from(MULTIPLIER_PDA_SEED)],
+        program.programId
+      );
+
+    const [_historyPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from(REWARD_HISTORY_PDA_SEED)],
+      program.programId
+    );
+
+    rewardPDA = _rewardPDA;
+    historyPDA = _historyPDA;
+    nftMultiplierPDA = _nftMultiplierPDA;
+
     // Generate player account PDA
     const [_playerPDA, _playerBump] =
       await anchor.web3.PublicKey.findProgramAddress(
@@ -134,7 +256,7 @@ describe('Membrane', () => {
       storage.publicKey.toBase58()
     );
     expect(associatedTokenAddress.toBase58()).to.equal(
-      storageTokenAddress.toBase58()
+      treasuryTokenAddress.toBase58()
     );
     expect(tokenBalance.value.decimals).to.equal(PLASMA_DECIMALS);
     expect(parseFloat(tokenBalance.value.uiAmountString)).to.equal(
@@ -149,6 +271,55 @@ describe('Membrane', () => {
     );
   });
 
+  it('Can initialize config', async () => {
+    await program.methods
+      .initializeConfig(maxSupply, dailyMintCap, emissionPerDay)
+      .accounts({
+        config: configPDA,
+        storage: storage.publicKey,
+        mint: mintAddress,
+        vaultToken: storageTokenAddress,
+        systemProgram
+      })
+      .signers([storage])
+      .rpc();
+
+    const configAccount = await program.account.config.fetch(configPDA);
+
+    expect(configAccount.admin.toBase58()).to.equal(
+      storage.publicKey.toBase58()
+    );
+    expect(configAccount.mint.toBase58()).to.equal(mintAddress.toBase58());
+    expect(configAccount.vault.toBase58()).to.equal(
+      storageTokenAddress.toBase58()
+    );
+    expect(configAccount.maxSupply.eq(maxSupply)).to.be.true;
+    expect(configAccount.dailyMintCap.eq(dailyMintCap)).to.be.true;
+    expect(configAccount.emissionPerDay.eq(emissionPerDay)).to.be.true;
+    expect(configAccount.totalMinted.toNumber()).to.equal(0);
+  });
+
+  it('Can initialize the economy parameters', async () => {
+    const signature = await program.methods
+      .initializeParams(economy)
+      .accounts({
+        params: paramsPDA,
+        storage: storage.publicKey,
+        config: configPDA,
+        systemProgram
+      })
+      .signers([storage])
+      .rpc();
+
+    const paramsAccount = await program.account.params.fetch(paramsPDA);
+    for (const key in economy) {
+      expect(paramsAccount.economy[key].eq(economy[key])).to.be.true;
+    }
+
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('ParamsUpdated');
+  });
+
   it('Can transfer authority to the PDA', async () => {
     const mintInfoBefore = await getMint(
       anchorProvider.connection,
@@ -172,7 +343,8 @@ describe('Membrane', () => {
         storage: storage.publicKey,
         storageTokenAccount: storageTokenAddress,
         mint: mintAddress,
-        tokenProgram: TOKEN_PROGRAM_ID
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: configPDA
       })
       .signers([storage])
       .rpc();
@@ -189,71 +361,359 @@ describe('Membrane', () => {
     expect(tokenAccountAfter.owner.toBase58()).to.equal(storagePDA.toBase58());
   });
 
+  it('Can rotate the admin in two steps', async () => {
+    const newAdmin = Keypair.generate();
+    const stranger = Keypair.generate();
+    const proposeAdmin = (admin: Keypair, pendingAdmin: PublicKey) =>
+      program.methods
+        .proposeAdmin(pendingAdmin)
+        .accounts({
+          config: configPDA,
+          storage: admin.publicKey,
+          authority: storagePDA,
+          mint: mintAddress,
+          vaultToken: storageTokenAddress
+        })
+        .signers([admin])
+        .rpc();
+    const acceptAdmin = (admin: Keypair) =>
+      program.methods
+        .acceptAdmin()
+        .accounts({
+          config: configPDA,
+          newAdmin: admin.publicKey
+        })
+        .signers([admin])
+        .rpc();
+
+    await proposeAdmin(storage, newAdmin.publicKey);
+
+    let configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.admin.toBase58()).to.equal(
+      storage.publicKey.toBase58()
+    );
+    expect(configAccount.pendingAdmin.toBase58()).to.equal(
+      newAdmin.publicKey.toBase58()
+    );
+
+    // Only the proposed key can accept
+    try {
+      await acceptAdmin(stranger);
+      expect.fail('accept_admin should reject a key other than the proposed one');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('NotPendingAdmin');
+    }
+
+    await acceptAdmin(newAdmin);
+
+    configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.admin.toBase58()).to.equal(
+      newAdmin.publicKey.toBase58()
+    );
+    expect(configAccount.pendingAdmin).to.be.null;
+
+    // Roles held by the old key move to the new admin
+    for (const role of [
+      configAccount.oracle,
+      configAccount.treasurer,
+      configAccount.paramManager
+    ]) {
+      expect(role.toBase58()).to.equal(newAdmin.publicKey.toBase58());
+    }
+
+    // Authorities stay with the vault PDA
+    const mintInfo = await getMint(anchorProvider.connection, mintAddress);
+    const vaultAccount = await getAccount(
+      anchorProvider.connection,
+      storageTokenAddress
+    );
+    expect(mintInfo.mintAuthority?.toBase58()).to.equal(storagePDA.toBase58());
+    expect(vaultAccount.owner.toBase58()).to.equal(storagePDA.toBase58());
+
+    // The old key lost its rights
+    try {
+      await proposeAdmin(storage, storage.publicKey);
+      expect.fail('propose_admin should reject the previous admin');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('Unauthorized');
+    }
+
+    // Rotate back for the rest of the suite
+    await proposeAdmin(newAdmin, storage.publicKey);
+    await acceptAdmin(storage);
+
+    configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.admin.toBase58()).to.equal(
+      storage.publicKey.toBase58()
+    );
+    expect(configAccount.treasurer.toBase58()).to.equal(
+      storage.publicKey.toBase58()
+    );
+  });
+
+  it('Can split the privileged instructions between roles', async () => {
+    const oracle = Keypair.generate();
+    const treasurer = Keypair.generate();
+    const paramManager = Keypair.generate();
+    const setRoles = (
+      admin: Keypair,
+      oracleKey: PublicKey,
+      treasurerKey: PublicKey,
+      paramManagerKey: PublicKey
+    ) =>
+      program.methods
+        .setRoles(oracleKey, treasurerKey, paramManagerKey)
+        .accounts({
+          config: configPDA,
+          storage: admin.publicKey
+        })
+        .signers([admin])
+        .rpc();
+
+    // Only the admin assigns roles
+    try {
+      await setRoles(
+        oracle,
+        oracle.publicKey,
+        oracle.publicKey,
+        oracle.publicKey
+      );
+      expect.fail('set_roles should reject a signer other than the admin');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('Unauthorized');
+    }
+
+    await setRoles(
+      storage,
+      oracle.publicKey,
+      treasurer.publicKey,
+      paramManager.publicKey
+    );
+
+    const configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.oracle.toBase58()).to.equal(
+      oracle.publicKey.toBase58()
+    );
+    expect(configAccount.treasurer.toBase58()).to.equal(
+      treasurer.publicKey.toBase58()
+    );
+    expect(configAccount.paramManager.toBase58()).to.equal(
+      paramManager.publicKey.toBase58()
+    );
+
+    // The game server cannot mint
+    try {
+      await program.methods
+        .mintToken(new anchor.BN(1))
+        .accounts({
+          params: paramsPDA,
+          mint: mintAddress,
+          tokenAccount: storageTokenAddress,
+          authority: storagePDA,
+          storage: oracle.publicKey,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([oracle])
+        .rpc();
+      expect.fail('mint_token should reject the oracle');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MissingRole');
+    }
+
+    // The treasurer cannot create games
+    await getAirdrop(anchorProvider.connection, treasurer.publicKey, 1);
+    const [roleGamePDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from('game'), Buffer.from('6')],
+      program.programId
+    );
+    try {
+      await program.methods
+        .createGame(new anchor.BN(6), [])
+        .accounts({
+          game: roleGamePDA,
+          storage: treasurer.publicKey,
+          config: configPDA,
+          systemProgram
+        })
+        .signers([treasurer])
+        .rpc();
+      expect.fail('create_game should reject the treasurer');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MissingRole');
+    }
+
+    // The admin is not any of the roles anymore
+    try {
+      await program.methods
+        .proposeParams(economy)
+        .accounts({
+          proposal: proposalPDA,
+          storage: storage.publicKey,
+          config: configPDA,
+          params: paramsPDA,
+          systemProgram
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('propose_params should reject a key without the role');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MissingRole');
+    }
+
+    // The admin can still veto a change of the parameter manager
+    await getAirdrop(anchorProvider.connection, paramManager.publicKey, 1);
+    await program.methods
+      .proposeParams(economy)
+      .accounts({
+        proposal: proposalPDA,
+        storage: paramManager.publicKey,
+        config: configPDA,
+        params: paramsPDA,
+        systemProgram
+      })
+      .signers([paramManager])
+      .rpc();
+    await program.methods
+      .cancelChange()
+      .accounts({
+        proposal: proposalPDA,
+        storage: storage.publicKey,
+        config: configPDA
+      })
+      .signers([storage])
+      .rpc();
+    expect(await anchorProvider.connection.getAccountInfo(proposalPDA)).to.be
+      .null;
+
+    // Back to a single key for the rest of the suite
+    await setRoles(
+      storage,
+      storage.publicKey,
+      storage.publicKey,
+      storage.publicKey
+    );
+  });
+
   it('Can mint a token', async () => {
     const amountToMint = new anchor.BN(adjustSupply(1000, PLASMA_DECIMALS));
 
-    const storageTokenBalanceBefore =
+    const treasuryTokenBalanceBefore =
       await anchorProvider.connection.getTokenAccountBalance(
-        storageTokenAddress
+        treasuryTokenAddress
       );
 
     await program.methods
       .mintToken(amountToMint)
       .accounts({
+        params: paramsPDA,
         mint: mintAddress,
-        tokenAccount: storageTokenAddress,
+        tokenAccount: treasuryTokenAddress,
         authority: storagePDA,
+        storage: storage.publicKey,
+        config: configPDA,
         tokenProgram: TOKEN_PROGRAM_ID
       })
-      .signers([])
+      .signers([storage])
       .rpc();
 
-    const storageTokenBalanceAfter =
+    const treasuryTokenBalanceAfter =
       await anchorProvider.connection.getTokenAccountBalance(
-        storageTokenAddress
+        treasuryTokenAddress
       );
 
     expect(
-      new anchor.BN(storageTokenBalanceBefore.value.amount)
+      new anchor.BN(treasuryTokenBalanceBefore.value.amount)
         .add(amountToMint)
-        .eq(new anchor.BN(storageTokenBalanceAfter.value.amount))
+        .eq(new anchor.BN(treasuryTokenBalanceAfter.value.amount))
     ).to.be.true;
+
+    const configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.totalMinted.eq(amountToMint)).to.be.true;
+    expect(configAccount.mintedToday.eq(amountToMint)).to.be.true;
   });
 
-  it('Can initialize a reward', async () => {
-    // Generate reward account
-    reward = Keypair.generate();
-    nftMultiplier = Keypair.generate();
+  it('Cannot mint without the configured storage', async () => {
+    const impostor = Keypair.generate();
+
+    try {
+      await program.methods
+        .mintToken(new anchor.BN(1))
+        .accounts({
+          params: paramsPDA,
+          mint: mintAddress,
+          tokenAccount: storageTokenAddress,
+          authority: storagePDA,
+          storage: impostor.publicKey,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([impostor])
+        .rpc();
+      expect.fail('mint_token should reject a non-admin signer');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MissingRole');
+    }
+  });
+
+  it('Cannot mint past the daily emission budget', async () => {
+    try {
+      await program.methods
+        .mintToken(dailyMintCap)
+        .accounts({
+          params: paramsPDA,
+          mint: mintAddress,
+          tokenAccount: storageTokenAddress,
+          authority: storagePDA,
+          storage: storage.publicKey,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('mint_token should enforce the daily emission budget');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('DailyMintCapExceeded');
+    }
+  });
 
+  it('Can initialize a reward', async () => {
     await program.methods
       .initializeNftMultiplier()
       .accounts({
-        nftMultiplier: nftMultiplier.publicKey,
+        nftMultiplier: nftMultiplierPDA,
         payer: storage.publicKey,
+        config: configPDA,
         systemProgram
       })
-      .signers([storage, nftMultiplier])
+      .signers([storage])
       .rpc();
 
-    const nftMultiplierAccount = await program.account.qualityMultiplier.fetch(nftMultiplier.publicKey);
+    const nftMultiplierAccount = await program.account.qualityMultiplier.fetch(nftMultiplierPDA);
 
     expect(nftMultiplierAccount).to.be.an('object');
-    // TODO: multiplier can be different than "common"
-    expect(nftMultiplierAccount?.common.toNumber()).to.be.equal(NFT_GRADE_MULTIPLIERS.COMMON);
+    for (const grade in NFT_GRADE_MULTIPLIERS) {
+      expect(nftMultiplierAccount[grade.toLowerCase()].toNumber()).to.be.equal(
+        NFT_GRADE_MULTIPLIERS[grade]
+      );
+    }
 
     await program.methods
       .initializeReward()
       .accounts({
-        reward: reward.publicKey,
-        nftMultiplier: nftMultiplier.publicKey,
+        params: paramsPDA,
+        reward: rewardPDA,
+        history: historyPDA,
+        nftMultiplier: nftMultiplierPDA,
         payer: storage.publicKey,
+        config: configPDA,
         systemProgram
       })
-      .signers([storage, reward])
+      .signers([storage])
       .rpc();
 
     // Initial reward
-    const rewardAccount = await program.account.reward.fetch(reward.publicKey);
+    const rewardAccount = await program.account.reward.fetch(rewardPDA);
 
     // Example result
     // {
@@ -263,16 +723,322 @@ describe('Membrane', () => {
     //   kill: 1249761085
     // }
 
-    const rewardMock = calculateInitialRewardParams(
-      nftMultiplierAccount.common.toNumber(),
-      PLASMA_DECIMALS
+    for (const grade in NFT_GRADE_MULTIPLIERS) {
+      const quality = grade.toLowerCase();
+      const rewardMock = calculateInitialRewardParams(
+        nftMultiplierAccount[quality].toNumber()
+      );
+
+      for (const key in rewardMock) {
+        const result = rewardAccount[quality][key];
+        const mock = rewardMock[key];
+        expect(result.eq(mock)).to.be.true;
+      }
+    }
+
+    // The table of the day is kept in the history
+    const historyAccount = await program.account.rewardHistory.fetch(
+      historyPDA
+    );
+    const days = rewardAccount.days.toNumber();
+    const snapshot = historyAccount.snapshots[days % REWARD_HISTORY_LEN];
+
+    expect(historyAccount.snapshots).to.have.lengthOf(REWARD_HISTORY_LEN);
+    expect(snapshot.days.toNumber()).to.equal(days);
+    expect(snapshot.multiplier.eq(rewardMultiplier(days))).to.be.true;
+    for (const grade in NFT_GRADE_MULTIPLIERS) {
+      const quality = grade.toLowerCase();
+      for (const key in rewardAccount[quality]) {
+        expect(snapshot[quality][key].eq(rewardAccount[quality][key])).to.be
+          .true;
+      }
+    }
+  });
+
+  it('Cannot initialize the reward table twice', async () => {
+    let failed = false;
+    try {
+      await program.methods
+        .initializeReward()
+        .accounts({
+          params: paramsPDA,
+          reward: rewardPDA,
+          history: historyPDA,
+          nftMultiplier: nftMultiplierPDA,
+          payer: storage.publicKey,
+          config: configPDA,
+          systemProgram
+        })
+        .signers([storage])
+        .rpc();
+    } catch (err) {
+      failed = true;
+    }
+    expect(failed).to.be.true;
+  });
+
+  it('Cannot roll the reward day twice a day', async () => {
+    const rewardAccount = await program.account.reward.fetch(rewardPDA);
+    const days = Math.floor((Date.now() / 1000 - START) / SEC_IN_DAY);
+    expect(rewardAccount.days.toNumber()).to.equal(days);
+
+    // No signer is needed, anyone can crank the rollover
+    try {
+      await program.methods
+        .rollRewardDay()
+        .accounts({
+          params: paramsPDA,
+          reward: rewardPDA,
+          history: historyPDA,
+          nftMultiplier: nftMultiplierPDA
+        })
+        .rpc();
+      expect.fail('roll_reward_day should run once per day');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('RewardTableUpToDate');
+    }
+  });
+
+  it('Can propose and cancel a timelocked parameter change', async () => {
+    const proposeAccounts = {
+      proposal: proposalPDA,
+      storage: storage.publicKey,
+      config: configPDA,
+      params: paramsPDA,
+      systemProgram
+    };
+    const cancelChange = () =>
+      program.methods
+        .cancelChange()
+        .accounts({
+          proposal: proposalPDA,
+          storage: storage.publicKey,
+          config: configPDA
+        })
+        .signers([storage])
+        .rpc();
+    const executeChange = () =>
+      program.methods
+        .executeChange()
+        .accounts({
+          proposal: proposalPDA,
+          proposer: storage.publicKey,
+          config: configPDA,
+          params: paramsPDA,
+          nftMultiplier: nftMultiplierPDA
+        })
+        .rpc();
+
+    // Values out of range are rejected up front
+    try {
+      await program.methods
+        .proposeParams({ ...economy, secInDay: new anchor.BN(0) })
+        .accounts(proposeAccounts)
+        .signers([storage])
+        .rpc();
+      expect.fail('propose_params should reject a value out of range');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidParams');
+    }
+
+    // The days are fixed, a shorter day would multiply the emission allowance already counted
+    const reserves = async (): Promise<anchor.BN> => {
+      const { events } = await program.methods
+        .checkSolvency()
+        .accounts({
+          config: configPDA,
+          params: paramsPDA,
+          vaultToken: storageTokenAddress,
+          mint: mintAddress
+        })
+        .simulate();
+      return events[0].data.reserves;
+    };
+    const reservesBefore = await reserves();
+    for (const changed of [
+      { ...economy, secInDay: new anchor.BN(SEC_IN_DAY / 24) },
+      { ...economy, start: economy.start.subn(SEC_IN_DAY) }
+    ]) {
+      try {
+        await program.methods
+          .proposeParams(changed)
+          .accounts(proposeAccounts)
+          .signers([storage])
+          .rpc();
+        expect.fail('propose_params should keep the start and the day length');
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal('ImmutableDays');
+      }
+    }
+    expect((await reserves()).eq(reservesBefore)).to.be.true;
+
+    const proposed = { ...economy, feeLamports: new anchor.BN(0) };
+    const signature = await program.methods
+      .proposeParams(proposed)
+      .accounts(proposeAccounts)
+      .signers([storage])
+      .rpc();
+
+    const proposalAccount = await program.account.proposal.fetch(proposalPDA);
+    const now = Math.floor(Date.now() / 1000);
+    expect(proposalAccount.change.params.economy.feeLamports.toNumber()).to
+      .equal(0);
+    expect(proposalAccount.proposer.toBase58()).to.equal(
+      storage.publicKey.toBase58()
     );
+    expect(proposalAccount.effectiveAt.toNumber()).to.be.greaterThan(
+      now + TIMELOCK_DELAY - 60
+    );
+
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('ChangeProposed');
+    expect(event.data.effectiveAt.eq(proposalAccount.effectiveAt)).to.be.true;
+
+    // Anyone can execute, but only after the delay
+    try {
+      await executeChange();
+      expect.fail('execute_change should wait for the timelock');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('TimelockNotExpired');
+    }
+
+    // The rent of the proposal goes back to the parameter manager that paid it
+    const stranger = Keypair.generate();
+    try {
+      await program.methods
+        .executeChange()
+        .accounts({
+          proposal: proposalPDA,
+          proposer: stranger.publicKey,
+          config: configPDA,
+          params: paramsPDA,
+          nftMultiplier: nftMultiplierPDA
+        })
+        .rpc();
+      expect.fail('execute_change should refund the proposer only');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('Unauthorized');
+    }
+
+    // Only the admin can cancel
+    try {
+      await program.methods
+        .cancelChange()
+        .accounts({
+          proposal: proposalPDA,
+          storage: stranger.publicKey,
+          config: configPDA
+        })
+        .signers([stranger])
+        .rpc();
+      expect.fail('cancel_change should reject a signer other than the admin');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MissingRole');
+    }
+
+    await cancelChange();
+    expect(await anchorProvider.connection.getAccountInfo(proposalPDA)).to.be
+      .null;
+
+    const paramsAccount = await program.account.params.fetch(paramsPDA);
+    expect(paramsAccount.economy.feeLamports.eq(economy.feeLamports)).to.be
+      .true;
+
+    // Nft multipliers go through the same flow
+    const stats = {
+      league: new anchor.BN(1_000_000_000),
+      victory: new anchor.BN(10_000_000),
+      topfive: new anchor.BN(40_000_000),
+      topten: new anchor.BN(50_000_000),
+      kills: new anchor.BN(1_000_000_000)
+    };
+    const nfts = {
+      common: {
+        durability: new anchor.BN(100),
+        payback: new anchor.BN(1_200_000_000)
+      },
+      rare: {
+        durability: new anchor.BN(120),
+        payback: new anchor.BN(1_350_000_000)
+      },
+      epic: {
+        durability: new anchor.BN(140),
+        payback: new anchor.BN(1_500_000_000)
+      },
+      legendary: {
+        durability: new anchor.BN(160),
+        payback: new anchor.BN(2_000_000_000)
+      }
+    };
+
+    // A zero multiplier would break every later roll of the reward table
+    try {
+      await program.methods
+        .proposeNftMultiplier(stats, {
+          ...nfts,
+          common: { ...nfts.common, durability: new anchor.BN(0) }
+        })
+        .accounts(proposeAccounts)
+        .signers([storage])
+        .rpc();
+      expect.fail('propose_nft_multiplier should reject a zero multiplier');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidParams');
+    }
+
+    await program.methods
+      .proposeNftMultiplier(stats, nfts)
+      .accounts(proposeAccounts)
+      .signers([storage])
+      .rpc();
+
+    const multiplierProposal = await program.account.proposal.fetch(
+      proposalPDA
+    );
+    expect(multiplierProposal.change).to.have.property('nftMultiplier');
+
+    await cancelChange();
+  });
 
-    for (const key in rewardMock) {
-      const result = rewardAccount[key];
-      const mock = rewardMock[key];
-      expect(result.eq(mock)).to.be.true;
+  it('Can propose an emission schedule within the daily mint cap', async () => {
+    const proposeEmission = (perDay: anchor.BN) =>
+      program.methods
+        .proposeEmission(perDay)
+        .accounts({
+          proposal: proposalPDA,
+          storage: storage.publicKey,
+          config: configPDA,
+          params: paramsPDA,
+          systemProgram
+        })
+        .signers([storage])
+        .rpc();
+
+    try {
+      await proposeEmission(dailyMintCap.add(new anchor.BN(1)));
+      expect.fail('propose_emission should reject a rate above the daily cap');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidParams');
     }
+
+    await proposeEmission(dailyMintCap);
+
+    const proposalAccount = await program.account.proposal.fetch(proposalPDA);
+    expect(proposalAccount.change.emission.perDay.eq(dailyMintCap)).to.be.true;
+
+    // The initial rate keeps running until the change is executed
+    const configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.emissionPerDay.eq(emissionPerDay)).to.be.true;
+
+    await program.methods
+      .cancelChange()
+      .accounts({
+        proposal: proposalPDA,
+        storage: storage.publicKey,
+        config: configPDA
+      })
+      .signers([storage])
+      .rpc();
   });
 
   it('Can initialize a player', async () => {
@@ -283,9 +1049,30 @@ describe('Membrane', () => {
       storage.publicKey
     );
 
+    // The registration fee cannot be paid to the user itself
+    try {
+      await program.methods
+        .initializePlayer(rating)
+        .accounts({
+          params: paramsPDA,
+          config: configPDA,
+          player: playerPDA,
+          authority: storagePDA,
+          storage: user.publicKey,
+          user: user.publicKey,
+          systemProgram
+        })
+        .rpc();
+      expect.fail('initialize_player should reject a fee receiver other than the admin');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('Unauthorized');
+    }
+
     await program.methods
       .initializePlayer(rating)
       .accounts({
+        params: paramsPDA,
+        config: configPDA,
         player: playerPDA,
         authority: storagePDA,
         storage: storage.publicKey,
@@ -312,41 +1099,251 @@ describe('Membrane', () => {
   });
 
   it('Can start a game', async () => {
-    await program.methods
-      .startGame(identifier)
-      .accounts({
-        game: gamePDA,
-        storage: storage.publicKey,
-        systemProgram
-      })
+    const accounts = {
+      game: gamePDA,
+      storage: storage.publicKey,
+      config: configPDA,
+      systemProgram
+    };
+    const createSignature = await program.methods
+      .createGame(identifier, [anchorProvider.wallet.publicKey])
+      .accounts(accounts)
       .signers([storage])
       .rpc();
 
-    const gameAccount = await program.account.gameStart.fetch(gamePDA);
+    const createdAccount = await program.account.game.fetch(gamePDA);
+    expect(createdAccount?.identifier.eq(identifier)).to.be.true;
+    expect(createdAccount?.rosterSize).to.equal(1);
+    expect(createdAccount?.roster).to.have.lengthOf(MAX_PLAYERS);
+    expect(createdAccount?.status).to.equal(GAME_STATUS.CREATED);
+
+    const [createEvent] = await getEvents(program, createSignature);
+    expect(createEvent.name).to.equal('GameCreated');
+    expect(createEvent.data.identifier.eq(identifier)).to.be.true;
+    expect(createEvent.data.roster[0].toBase58()).to.equal(
+      anchorProvider.wallet.publicKey.toBase58()
+    );
+
+    const startGame = () =>
+      program.methods
+        .startGame(identifier, gameBump)
+        .accounts(accounts)
+        .signers([storage])
+        .rpc();
+    const signature = await startGame();
+
+    const gameAccount = await program.account.game.fetch(gamePDA);
+    expect(gameAccount?.startTimestamp.toNumber()).to.be.a('number');
+    expect(gameAccount?.status).to.equal(GAME_STATUS.RUNNING);
+
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('GameStarted');
+    expect(event.data.identifier.eq(identifier)).to.be.true;
+    expect(event.data.rosterSize).to.equal(1);
+    expect(event.data.startTimestamp.eq(gameAccount.startTimestamp)).to.be.true;
+
+    try {
+      await startGame();
+      expect.fail('start_game should not run twice');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('GameAlreadyStarted');
+    }
 
-    expect(gameAccount?.timestamp.toNumber()).to.be.a('number');
+    // The roster is fixed once the game runs
+    try {
+      await program.methods
+        .extendRoster(identifier, gameBump, [Keypair.generate().publicKey])
+        .accounts(accounts)
+        .signers([storage])
+        .rpc();
+      expect.fail('extend_roster should reject a running game');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('GameAlreadyStarted');
+    }
   });
 
-  it('Can make a single payout', async () => {
-    const user = anchorProvider.wallet;
-    const { placement, kills } = generateRandomGameResult();
+  it('Cannot start a game without the configured storage', async () => {
+    const impostor = Keypair.generate();
+    await getAirdrop(anchorProvider.connection, impostor.publicKey, 1);
 
-    // Generate game account PDA
-    const [playersStatsPDA, playersStatsBump] =
+    const otherIdentifier = new anchor.BN(1);
+    const [otherGamePDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from('game'), Buffer.from(otherIdentifier.toString())],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .createGame(otherIdentifier, [])
+        .accounts({
+          game: otherGamePDA,
+          storage: impostor.publicKey,
+          config: configPDA,
+          systemProgram
+        })
+        .signers([impostor])
+        .rpc();
+      expect.fail('create_game should reject a non-admin signer');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MissingRole');
+    }
+  });
+
+  it('Cannot make a payout before the game ends', async () => {
+    const { placement, kills } = generateRandomGameResult(players);
+
+    try {
+      await program.methods
+        .calculateReward(
+          placement.toNumber(),
+          kills.toNumber(),
+          { common: {} },
+          identifier,
+          gameBump
+        )
+        .accounts({
+          params: paramsPDA,
+          reward: rewardPDA,
+          mint: mintAddress,
+          vaultToken: storageTokenAddress,
+          authority: storagePDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          player: playerPDA,
+          storage: storage.publicKey,
+          config: configPDA,
+          game: gamePDA,
+          systemProgram
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('calculate_reward should reject a running game');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('GameNotEnded');
+    }
+  });
+
+  it('Can end a game', async () => {
+    const endGame = () =>
+      program.methods
+        .endGame(identifier, gameBump)
+        .accounts({
+          storage: storage.publicKey,
+          config: configPDA,
+          systemProgram,
+          game: gamePDA
+        })
+        .signers([storage])
+        .rpc();
+
+    const signature = await endGame();
+
+    const gameAccount = await program.account.game.fetch(gamePDA);
+    const [event] = await getEvents(program, signature);
+
+    expect(event.name).to.equal('GameEnded');
+    expect(event.data.endTimestamp.eq(gameAccount.endTimestamp)).to.be.true;
+
+    expect(gameAccount?.endTimestamp.toNumber()).to.be.a('number');
+    expect(gameAccount?.status).to.equal(GAME_STATUS.ENDED);
+
+    try {
+      await endGame();
+      expect.fail('end_game should not run twice');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('GameNotRunning');
+    }
+  });
+
+  it('Can cancel a running game', async () => {
+    const cancelIdentifier = new anchor.BN(3);
+    const [cancelGamePDA, cancelGameBump] =
       await anchor.web3.PublicKey.findProgramAddress(
-        [Buffer.from('players'), Buffer.from(identifier.toString())],
+        [Buffer.from('game'), Buffer.from(cancelIdentifier.toString())],
         program.programId
       );
+    const accounts = {
+      storage: storage.publicKey,
+      config: configPDA,
+      systemProgram,
+      game: cancelGamePDA
+    };
 
-    const playerAccountBefore = await program.account.player.fetch(playerPDA);
+    await program.methods
+      .createGame(cancelIdentifier, [])
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
 
+    // A game without players could never be settled
+    try {
+      await program.methods
+        .startGame(cancelIdentifier, cancelGameBump)
+        .accounts(accounts)
+        .signers([storage])
+        .rpc();
+      expect.fail('start_game should reject an empty roster');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('EmptyRoster');
+    }
+
+    await program.methods
+      .extendRoster(cancelIdentifier, cancelGameBump, [
+        Keypair.generate().publicKey
+      ])
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .startGame(cancelIdentifier, cancelGameBump)
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
     await program.methods
-      .calculateReward(placement, kills, identifier)
+      .cancelGame(cancelIdentifier, cancelGameBump)
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+
+    const gameAccount = await program.account.game.fetch(cancelGamePDA);
+    expect(gameAccount?.status).to.equal(GAME_STATUS.CANCELLED);
+
+    try {
+      await program.methods
+        .endGame(cancelIdentifier, cancelGameBump)
+        .accounts(accounts)
+        .signers([storage])
+        .rpc();
+      expect.fail('end_game should reject a cancelled game');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('GameNotRunning');
+    }
+  });
+
+  it('Can make a single payout', async () => {
+    const user = anchorProvider.wallet;
+    const { placement, kills } = generateRandomGameResult(players);
+
+    const playerAccountBefore = await program.account.player.fetch(playerPDA);
+
+    const signature = await program.methods
+      .calculateReward(
+        placement.toNumber(),
+        kills.toNumber(),
+        { epic: {} },
+        identifier,
+        gameBump
+      )
       .accounts({
-        reward: reward.publicKey,
+        params: paramsPDA,
+        reward: rewardPDA,
+        mint: mintAddress,
+        vaultToken: storageTokenAddress,
+        authority: storagePDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
         player: playerPDA,
-        playersStats: playersStatsPDA,
         storage: storage.publicKey,
+        config: configPDA,
+        game: gamePDA,
         systemProgram
       })
       .signers([storage])
@@ -354,13 +1351,10 @@ describe('Membrane', () => {
 
     const playerAccountAfter = await program.account.player.fetch(playerPDA);
 
-    const playersStatsAccountAfter = await program.account.playersStats.fetch(
-      playersStatsPDA
-    );
+    const playersStatsAccountAfter = await program.account.game.fetch(gamePDA);
 
     const rewardMock = calculateInitialRewardParams(
-      NFT_GRADE_MULTIPLIERS.COMMON,
-      PLASMA_DECIMALS
+      NFT_GRADE_MULTIPLIERS.EPIC
     );
     const { rewardAmount, ratingChange } = calculatePlayerPayout(
       placement,
@@ -399,15 +1393,790 @@ describe('Membrane', () => {
     expect(
       playerAccountBefore.rating.add(safeRatingChange).toNumber()
     ).to.be.equal(playerAccountAfter.rating.toNumber());
+
+    const event = (await getEvents(program, signature)).find(
+      (event) => event.name === 'RewardCredited'
+    );
+    expect(event?.data.player.toBase58()).to.equal(user.publicKey.toBase58());
+    expect(event?.data.placement).to.equal(placement.toNumber());
+    expect(event?.data.reward.eq(stat?.reward)).to.be.true;
+    expect(event?.data.rating.eq(playerAccountAfter.rating)).to.be.true;
+    expect(event?.data.claimable.eq(playerAccountAfter.claimable)).to.be.true;
   });
 
-  it('User can claim a reward', async () => {
+  it('Can top up the vault within the emission schedule', async () => {
     const user = anchorProvider.wallet;
-    const playerAccountBefore = await program.account.player.fetch(playerPDA);
+    const setPause = (paused: number) =>
+      program.methods
+        .setPause(paused)
+        .accounts({ config: configPDA, storage: storage.publicKey })
+        .signers([storage])
+        .rpc();
+    const vaultBalance = async () =>
+      new anchor.BN(
+        (
+          await anchorProvider.connection.getTokenAccountBalance(
+            storageTokenAddress
+          )
+        ).value.amount
+      );
 
-    const storageTokenBalanceBefore =
-      await anchorProvider.connection.getTokenAccountBalance(
-        storageTokenAddress
+    // The vault started empty, so it holds exactly what the schedule minted for the credits
+    let configAccount = await program.account.config.fetch(configPDA);
+    const vaultBefore = await vaultBalance();
+    expect(vaultBefore.eq(configAccount.emitted)).to.be.true;
+    expect(vaultBefore.eq(configAccount.totalClaimable)).to.be.true;
+    expect(configAccount.emitted.lte(emissionPerDay)).to.be.true;
+    const emittedBefore = configAccount.emitted;
+
+    // Credit a victory while minting is paused, the vault is not topped up
+    const emissionIdentifier = new anchor.BN(7);
+    const [emissionGamePDA, emissionGameBump] =
+      await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from('game'), Buffer.from(emissionIdentifier.toString())],
+        program.programId
+      );
+    const gameAccounts = {
+      storage: storage.publicKey,
+      config: configPDA,
+      systemProgram,
+      game: emissionGamePDA
+    };
+    await program.methods
+      .createGame(emissionIdentifier, [user.publicKey])
+      .accounts(gameAccounts)
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .startGame(emissionIdentifier, emissionGameBump)
+      .accounts(gameAccounts)
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .endGame(emissionIdentifier, emissionGameBump)
+      .accounts(gameAccounts)
+      .signers([storage])
+      .rpc();
+
+    await setPause(PAUSE.MINTING);
+    await program.methods
+      .calculateReward(
+        1,
+        2,
+        { common: {} },
+        emissionIdentifier,
+        emissionGameBump
+      )
+      .accounts({
+        ...gameAccounts,
+        params: paramsPDA,
+        reward: rewardPDA,
+        mint: mintAddress,
+        vaultToken: storageTokenAddress,
+        authority: storagePDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        player: playerPDA
+      })
+      .signers([storage])
+      .rpc();
+
+    configAccount = await program.account.config.fetch(configPDA);
+    expect((await vaultBalance()).eq(vaultBefore)).to.be.true;
+    expect(configAccount.emitted.eq(emittedBefore)).to.be.true;
+    expect(configAccount.totalClaimable.gt(vaultBefore)).to.be.true;
+
+    // A non-associated token account, the associated one is created by a later test
+    const userTokenAddress = await spl.createAccount(
+      anchorProvider.connection,
+      storage,
+      mintAddress,
+      user.publicKey,
+      Keypair.generate()
+    );
+    const claim = (amount: anchor.BN) =>
+      program.methods
+        .claim(amount)
+        .accounts({
+          player: playerPDA,
+          user: user.publicKey,
+          authority: storagePDA,
+          vaultToken: storageTokenAddress,
+          playerToken: userTokenAddress,
+          mint: mintAddress,
+          config: configPDA,
+          params: paramsPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY
+        })
+        .rpc();
+
+    // The claim needs more than the vault holds and the schedule cannot mint it
+    const playerAccount = await program.account.player.fetch(playerPDA);
+    try {
+      await claim(playerAccount.claimable);
+      expect.fail('claim should not pay out more than the vault and the schedule');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('EmissionScheduleExceeded');
+    }
+
+    // Once minting resumes, the claim mints only the shortfall into the vault
+    await setPause(0);
+    const amount = vaultBefore.addn(1);
+    const signature = await claim(amount);
+
+    configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.emitted.eq(emittedBefore.addn(1))).to.be.true;
+    expect((await vaultBalance()).isZero()).to.be.true;
+    const userTokenBalance =
+      await anchorProvider.connection.getTokenAccountBalance(userTokenAddress);
+    expect(new anchor.BN(userTokenBalance.value.amount).eq(amount)).to.be.true;
+
+    const [replenished] = await getEvents(program, signature);
+    expect(replenished.name).to.equal('VaultReplenished');
+    expect(replenished.data.amount.eqn(1)).to.be.true;
+    expect(replenished.data.emitted.eq(configAccount.emitted)).to.be.true;
+  });
+
+  it('Can settle a whole game at once', async () => {
+    const settleIdentifier = new anchor.BN(2);
+    const lobby = 4;
+
+    const [settleGamePDA, settleGameBump] =
+      await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from('game'), Buffer.from(settleIdentifier.toString())],
+        program.programId
+      );
+
+    const wallets: Keypair[] = [];
+    const playerPDAs: PublicKey[] = [];
+    for (let i = 0; i < lobby; i++) {
+      const wallet = Keypair.generate();
+      await getAirdrop(anchorProvider.connection, wallet.publicKey, 1);
+
+      const [walletPlayerPDA] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from('player'), wallet.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializePlayer(new anchor.BN(0))
+        .accounts({
+          params: paramsPDA,
+          config: configPDA,
+          player: walletPlayerPDA,
+          authority: storagePDA,
+          storage: storage.publicKey,
+          user: wallet.publicKey,
+          systemProgram
+        })
+        .signers([wallet])
+        .rpc();
+
+      wallets.push(wallet);
+      playerPDAs.push(walletPlayerPDA);
+    }
+
+    const settleGameAccounts = {
+      storage: storage.publicKey,
+      config: configPDA,
+      systemProgram,
+      game: settleGamePDA
+    };
+    await program.methods
+      .createGame(
+        settleIdentifier,
+        wallets.map((wallet) => wallet.publicKey)
+      )
+      .accounts(settleGameAccounts)
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .startGame(settleIdentifier, settleGameBump)
+      .accounts(settleGameAccounts)
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .endGame(settleIdentifier, settleGameBump)
+      .accounts(settleGameAccounts)
+      .signers([storage])
+      .rpc();
+
+    const results = generateGameResults(lobby).map((result, i) => ({
+      player: wallets[i].publicKey,
+      placement: result.placement.toNumber(),
+      kills: result.kills.toNumber(),
+      quality: { common: {} }
+    }));
+
+    const settleGame = (offset: number, chunk: number[]) =>
+      program.methods
+        .settleGame(
+          settleIdentifier,
+          settleGameBump,
+          offset,
+          chunk.map((i) => results[i])
+        )
+        .accounts({
+          params: paramsPDA,
+          reward: rewardPDA,
+          mint: mintAddress,
+          vaultToken: storageTokenAddress,
+          authority: storagePDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          storage: storage.publicKey,
+          config: configPDA,
+          game: settleGamePDA,
+          systemProgram
+        })
+        .remainingAccounts(
+          chunk.map((i) => ({
+            pubkey: playerPDAs[i],
+            isWritable: true,
+            isSigner: false
+          }))
+        )
+        .signers([storage])
+        .rpc();
+
+    // First chunk
+    await settleGame(0, [0, 1, 2]);
+
+    const gameAccountBefore = await program.account.game.fetch(settleGamePDA);
+    expect(gameAccountBefore.settled).to.equal(3);
+    expect(gameAccountBefore.status).to.equal(GAME_STATUS.ENDED);
+
+    // A player cannot be rewarded twice
+    try {
+      await settleGame(3, [0]);
+      expect.fail('settle_game should reject an already rewarded player');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('AlreadyRewarded');
+    }
+
+    // Second chunk
+    await settleGame(3, [3]);
+
+    const gameAccount = await program.account.game.fetch(settleGamePDA);
+    const stats = (gameAccount.players as Stat[]).slice(
+      0,
+      gameAccount.rosterSize
+    );
+
+    expect(gameAccount.settled).to.equal(lobby);
+    expect(gameAccount.status).to.equal(GAME_STATUS.SETTLED);
+    expect(stats).to.have.lengthOf(lobby);
+
+    let totalReward = new anchor.BN(0);
+    for (let i = 0; i < lobby; i++) {
+      const playerAccount = await program.account.player.fetch(playerPDAs[i]);
+      expect(stats[i].id.toBase58()).to.equal(wallets[i].publicKey.toBase58());
+      expect(stats[i].placement).to.equal(results[i].placement);
+      expect(playerAccount.claimable.eq(stats[i].reward)).to.be.true;
+      totalReward = totalReward.add(stats[i].reward);
+    }
+    expect(gameAccount.totalReward.eq(totalReward)).to.be.true;
+
+    // A settled game cannot be credited again
+    try {
+      await settleGame(lobby, [0]);
+      expect.fail('settle_game should reject a settled game');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('GameNotEnded');
+    }
+  });
+
+  it('Cannot reward a player outside of the roster', async () => {
+    const outsiderIdentifier = new anchor.BN(4);
+    const [outsiderGamePDA, outsiderGameBump] =
+      await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from('game'), Buffer.from(outsiderIdentifier.toString())],
+        program.programId
+      );
+    const accounts = {
+      storage: storage.publicKey,
+      config: configPDA,
+      systemProgram,
+      game: outsiderGamePDA
+    };
+
+    await program.methods
+      .createGame(outsiderIdentifier, [Keypair.generate().publicKey])
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .startGame(outsiderIdentifier, outsiderGameBump)
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .endGame(outsiderIdentifier, outsiderGameBump)
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+
+    try {
+      await program.methods
+        .calculateReward(
+          1,
+          0,
+          { common: {} },
+          outsiderIdentifier,
+          outsiderGameBump
+        )
+        .accounts({
+          ...accounts,
+          params: paramsPDA,
+          reward: rewardPDA,
+          mint: mintAddress,
+          vaultToken: storageTokenAddress,
+          authority: storagePDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          player: playerPDA
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('calculate_reward should reject a player outside the roster');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('NotInRoster');
+    }
+  });
+
+  it('Can settle a full lobby within the compute budget', async () => {
+    const lobbyIdentifier = new anchor.BN(5);
+    const rosterChunk = 25; // identities that fit into a single transaction
+    const settleChunk = 8; // MAX_SETTLE_BATCH
+
+    const [lobbyGamePDA, lobbyGameBump] =
+      await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from('game'), Buffer.from(lobbyIdentifier.toString())],
+        program.programId
+      );
+    const accounts = {
+      storage: storage.publicKey,
+      config: configPDA,
+      systemProgram,
+      game: lobbyGamePDA
+    };
+
+    const wallets: Keypair[] = [];
+    const playerPDAs: PublicKey[] = [];
+    for (let i = 0; i < MAX_PLAYERS; i++) {
+      const wallet = Keypair.generate();
+      await getAirdrop(anchorProvider.connection, wallet.publicKey, 1);
+
+      const [walletPlayerPDA] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from('player'), wallet.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializePlayer(new anchor.BN(0))
+        .accounts({
+          params: paramsPDA,
+          config: configPDA,
+          player: walletPlayerPDA,
+          authority: storagePDA,
+          storage: storage.publicKey,
+          user: wallet.publicKey,
+          systemProgram
+        })
+        .signers([wallet])
+        .rpc();
+
+      wallets.push(wallet);
+      playerPDAs.push(walletPlayerPDA);
+    }
+    const roster = wallets.map((wallet) => wallet.publicKey);
+
+    await program.methods
+      .createGame(lobbyIdentifier, roster.slice(0, rosterChunk))
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+    for (let i = rosterChunk; i < MAX_PLAYERS; i += rosterChunk) {
+      await program.methods
+        .extendRoster(
+          lobbyIdentifier,
+          lobbyGameBump,
+          roster.slice(i, i + rosterChunk)
+        )
+        .accounts(accounts)
+        .signers([storage])
+        .rpc();
+    }
+
+    // The lobby is full
+    try {
+      await program.methods
+        .extendRoster(lobbyIdentifier, lobbyGameBump, [
+          Keypair.generate().publicKey
+        ])
+        .accounts(accounts)
+        .signers([storage])
+        .rpc();
+      expect.fail('extend_roster should reject more than MAX_PLAYERS');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('PlayersOverflow');
+    }
+
+    await program.methods
+      .startGame(lobbyIdentifier, lobbyGameBump)
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .endGame(lobbyIdentifier, lobbyGameBump)
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+
+    const results = generateGameResults(MAX_PLAYERS).map((result, i) => ({
+      player: roster[i],
+      placement: result.placement.toNumber(),
+      kills: result.kills.toNumber(),
+      quality: { common: {} }
+    }));
+
+    const computeUnits: number[] = [];
+    for (let offset = 0; offset < MAX_PLAYERS; offset += settleChunk) {
+      const chunk = results.slice(offset, offset + settleChunk);
+      const signature = await program.methods
+        .settleGame(lobbyIdentifier, lobbyGameBump, offset, chunk)
+        .accounts({
+          ...accounts,
+          params: paramsPDA,
+          reward: rewardPDA,
+          mint: mintAddress,
+          vaultToken: storageTokenAddress,
+          authority: storagePDA,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .remainingAccounts(
+          playerPDAs.slice(offset, offset + settleChunk).map((pubkey) => ({
+            pubkey,
+            isWritable: true,
+            isSigner: false
+          }))
+        )
+        .signers([storage])
+        .rpc();
+
+      const units = await getComputeUnits(
+        anchorProvider.connection,
+        program.programId,
+        signature
+      );
+      computeUnits.push(units);
+      console.log(
+        `settle_game [${offset}, ${offset + chunk.length}): ${units} CU, ${Math.round(units / chunk.length)} CU per player`
+      );
+    }
+
+    const gameAccount = await program.account.game.fetch(lobbyGamePDA);
+    expect(gameAccount.rosterSize).to.equal(MAX_PLAYERS);
+    expect(gameAccount.settled).to.equal(MAX_PLAYERS);
+    expect(gameAccount.status).to.equal(GAME_STATUS.SETTLED);
+
+    // Every chunk stays well within the default budget of 200k CU
+    expect(Math.max(...computeUnits)).to.be.lessThan(200_000);
+  });
+
+  it('Can archive and close a settled game', async () => {
+    const findGame = (gameIdentifier: anchor.BN) =>
+      anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from('game'), Buffer.from(gameIdentifier.toString())],
+        program.programId
+      );
+    const closeGame = async (gameIdentifier: anchor.BN) => {
+      const [closeGamePDA, closeGameBump] = await findGame(gameIdentifier);
+      await program.methods
+        .closeGame(gameIdentifier, closeGameBump)
+        .accounts({
+          game: closeGamePDA,
+          storage: storage.publicKey,
+          config: configPDA
+        })
+        .signers([storage])
+        .rpc();
+    };
+
+    const settledIdentifier = new anchor.BN(2);
+    const [settledGamePDA, settledGameBump] = await findGame(settledIdentifier);
+    const [archivePDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from('archive'), Buffer.from(settledIdentifier.toString())],
+      program.programId
+    );
+
+    // Only a settled game can be archived
+    try {
+      const [endedGamePDA, endedGameBump] = await findGame(identifier);
+      await program.methods
+        .archiveGame(identifier, endedGameBump)
+        .accounts({
+          archive: (
+            await anchor.web3.PublicKey.findProgramAddress(
+              [Buffer.from('archive'), Buffer.from(identifier.toString())],
+              program.programId
+            )
+          )[0],
+          game: endedGamePDA,
+          storage: storage.publicKey,
+          config: configPDA,
+          systemProgram
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('archive_game should reject a game that is not settled');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('GameNotSettled');
+    }
+
+    const gameAccount = await program.account.game.fetch(settledGamePDA);
+
+    await program.methods
+      .archiveGame(settledIdentifier, settledGameBump)
+      .accounts({
+        archive: archivePDA,
+        game: settledGamePDA,
+        storage: storage.publicKey,
+        config: configPDA,
+        systemProgram
+      })
+      .signers([storage])
+      .rpc();
+
+    const archiveAccount = await program.account.gameArchive.fetch(archivePDA);
+    expect(archiveAccount.identifier.eq(settledIdentifier)).to.be.true;
+    expect(archiveAccount.totalReward.eq(gameAccount.totalReward)).to.be.true;
+    expect(archiveAccount.playerCount).to.equal(gameAccount.settled);
+    expect(archiveAccount.resultsHash).to.have.lengthOf(32);
+    expect(archiveAccount.endTimestamp.eq(gameAccount.endTimestamp)).to.be
+      .true;
+
+    const storageBalanceBefore = await anchorProvider.connection.getBalance(
+      storage.publicKey
+    );
+    await closeGame(settledIdentifier);
+    const storageBalanceAfter = await anchorProvider.connection.getBalance(
+      storage.publicKey
+    );
+
+    expect(storageBalanceAfter).to.be.greaterThan(storageBalanceBefore);
+    expect(await anchorProvider.connection.getAccountInfo(settledGamePDA)).to.be
+      .null;
+
+    // A cancelled game can be closed as well
+    await closeGame(new anchor.BN(3));
+
+    // An ended game still owes rewards
+    try {
+      await closeGame(new anchor.BN(4));
+      expect.fail('close_game should reject a game that is not settled');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('GameNotClosable');
+    }
+
+    // The roster member of game 4 has no player account, so the oracle cancels it to reclaim the rent
+    const [stuckGamePDA, stuckGameBump] = await findGame(new anchor.BN(4));
+    await program.methods
+      .cancelGame(new anchor.BN(4), stuckGameBump)
+      .accounts({
+        storage: storage.publicKey,
+        config: configPDA,
+        systemProgram,
+        game: stuckGamePDA
+      })
+      .signers([storage])
+      .rpc();
+    await closeGame(new anchor.BN(4));
+    expect(await anchorProvider.connection.getAccountInfo(stuckGamePDA)).to.be
+      .null;
+  });
+
+  it('User can claim a part of the reward into a new token account', async () => {
+    const user = anchorProvider.wallet;
+    const userTokenAddress = await findAssociatedTokenAddress(
+      user.publicKey,
+      mintAddress
+    );
+    const claimAccounts = {
+      player: playerPDA,
+      user: user.publicKey,
+      authority: storagePDA,
+      vaultToken: storageTokenAddress,
+      playerToken: userTokenAddress,
+      mint: mintAddress,
+      config: configPDA,
+      params: paramsPDA,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY
+    };
+    const playerAccountBefore = await program.account.player.fetch(playerPDA);
+    expect(playerAccountBefore.claimable.gtn(1)).to.be.true;
+    expect(
+      await anchorProvider.connection.getAccountInfo(userTokenAddress)
+    ).to.be.null;
+
+    try {
+      await program.methods
+        .claim(playerAccountBefore.claimable.addn(1))
+        .accounts(claimAccounts)
+        .rpc();
+      expect.fail('claim should reject more than the claimable reward');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidClaimAmount');
+    }
+
+    const amount = new anchor.BN(1);
+    const signature = await program.methods
+      .claim(amount)
+      .accounts(claimAccounts)
+      .rpc();
+
+    // The associated token account is created on the way
+    const userTokenBalance =
+      await anchorProvider.connection.getTokenAccountBalance(userTokenAddress);
+    expect(new anchor.BN(userTokenBalance.value.amount).eq(amount)).to.be.true;
+
+    const playerAccountAfter = await program.account.player.fetch(playerPDA);
+    expect(
+      playerAccountAfter.claimable.eq(playerAccountBefore.claimable.sub(amount))
+    ).to.be.true;
+
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('RewardClaimed');
+    expect(event.data.amount.eq(amount)).to.be.true;
+  });
+
+  it('User cannot claim a reward of another player', async () => {
+    const victim = anchorProvider.wallet;
+    const attacker = Keypair.generate();
+    await getAirdrop(anchorProvider.connection, attacker.publicKey, 1);
+
+    const [attackerPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from('player'), attacker.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializePlayer(new anchor.BN(0))
+      .accounts({
+        params: paramsPDA,
+        config: configPDA,
+        player: attackerPDA,
+        authority: storagePDA,
+        storage: storage.publicKey,
+        user: attacker.publicKey,
+        systemProgram
+      })
+      .signers([attacker])
+      .rpc();
+
+    const attackerTokenAccount = await getOrCreateAssociatedTokenAccount(
+      anchorProvider.connection,
+      storage,
+      mintAddress,
+      attacker.publicKey
+    );
+    const victimTokenAccount = await getOrCreateAssociatedTokenAccount(
+      anchorProvider.connection,
+      storage,
+      mintAddress,
+      victim.publicKey
+    );
+    const victimAccountBefore = await program.account.player.fetch(playerPDA);
+
+    // Victim's player account with the attacker's signature
+    try {
+      await program.methods
+        .userClaim()
+        .accounts({
+          player: playerPDA,
+          user: attacker.publicKey,
+          authority: storagePDA,
+          vaultToken: storageTokenAddress,
+          playerToken: attackerTokenAccount.address,
+          mint: mintAddress,
+          config: configPDA,
+          params: paramsPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY
+        })
+        .signers([attacker])
+        .rpc();
+      expect.fail('user_claim should reject a foreign player account');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('ConstraintSeeds');
+    }
+
+    // Attacker's own player account paying out to the victim's token account
+    try {
+      await program.methods
+        .userClaim()
+        .accounts({
+          player: attackerPDA,
+          user: attacker.publicKey,
+          authority: storagePDA,
+          vaultToken: storageTokenAddress,
+          playerToken: victimTokenAccount.address,
+          mint: mintAddress,
+          config: configPDA,
+          params: paramsPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY
+        })
+        .signers([attacker])
+        .rpc();
+      expect.fail('user_claim should reject a token account of another owner');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidOwner');
+    }
+
+    // Any token account of the signer's mint instead of the vault
+    try {
+      await program.methods
+        .userClaim()
+        .accounts({
+          player: playerPDA,
+          user: victim.publicKey,
+          authority: storagePDA,
+          vaultToken: attackerTokenAccount.address,
+          playerToken: victimTokenAccount.address,
+          mint: mintAddress,
+          config: configPDA,
+          params: paramsPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY
+        })
+        .rpc();
+      expect.fail('user_claim should reject a token account other than the vault');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidVault');
+    }
+
+    const victimAccountAfter = await program.account.player.fetch(playerPDA);
+    expect(victimAccountAfter.claimable.eq(victimAccountBefore.claimable)).to
+      .be.true;
+  });
+
+  it('User can claim a reward', async () => {
+    const user = anchorProvider.wallet;
+    const playerAccountBefore = await program.account.player.fetch(playerPDA);
+
+    const storageTokenBalanceBefore =
+      await anchorProvider.connection.getTokenAccountBalance(
+        storageTokenAddress
       );
     const userTokenAccount = await getOrCreateAssociatedTokenAccount(
       anchorProvider.connection,
@@ -420,7 +2189,7 @@ describe('Membrane', () => {
         userTokenAccount.address
       );
 
-    await program.methods
+    const signature = await program.methods
       .userClaim()
       .accounts({
         player: playerPDA,
@@ -429,7 +2198,12 @@ describe('Membrane', () => {
         vaultToken: storageTokenAddress,
         playerToken: userTokenAccount.address,
         mint: mintAddress,
-        tokenProgram: TOKEN_PROGRAM_ID
+        config: configPDA,
+        params: paramsPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY
       })
       .signers([]) // anchor will set user as a signer by default
       .rpc();
@@ -459,6 +2233,36 @@ describe('Membrane', () => {
       )
     ).to.be.true;
     expect(playerAccountAfter.claimable.eq(new anchor.BN(0))).to.be.true;
+
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('RewardClaimed');
+    expect(event.data.amount.eq(playerAccountBefore.claimable)).to.be.true;
+  });
+
+  it('Can check the solvency of the vault', async () => {
+    const { events } = await program.methods
+      .checkSolvency()
+      .accounts({
+        config: configPDA,
+        params: paramsPDA,
+        vaultToken: storageTokenAddress,
+        mint: mintAddress
+      })
+      .simulate();
+
+    const configAccount = await program.account.config.fetch(configPDA);
+    const vaultBalance = await anchorProvider.connection.getTokenAccountBalance(
+      storageTokenAddress
+    );
+
+    const [event] = events;
+    expect(event.name).to.equal('SolvencyChecked');
+    expect(event.data.totalClaimable.eq(configAccount.totalClaimable)).to.be
+      .true;
+    expect(event.data.vaultBalance.eq(new anchor.BN(vaultBalance.value.amount)))
+      .to.be.true;
+    expect(event.data.reserves.gte(event.data.vaultBalance)).to.be.true;
+    expect(event.data.solvent).to.be.true;
   });
 
   it('User can sell the token', async () => {
@@ -479,14 +2283,71 @@ describe('Membrane', () => {
     await program.methods
       .mintToken(amountToSell)
       .accounts({
+        params: paramsPDA,
         mint: mintAddress,
         tokenAccount: userTokenAccount.address,
         authority: storagePDA,
+        storage: storage.publicKey,
+        config: configPDA,
         tokenProgram: TOKEN_PROGRAM_ID
       })
-      .signers([])
+      .signers([storage])
       .rpc();
 
+    // Fund the treasury that pays the buyback
+    [treasuryPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from(TREASURY_PDA_SEED)],
+      program.programId
+    );
+    await anchorProvider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: storage.publicKey,
+          toPubkey: treasuryPDA,
+          lamports: anchor.web3.LAMPORTS_PER_SOL
+        })
+      ),
+      [storage]
+    );
+
+    const sellAccounts = {
+      player: user.publicKey,
+      mint: mintAddress,
+      vaultToken: storageTokenAddress,
+      playerToken: userTokenAccount.address,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      authority: storagePDA,
+      treasury: treasuryPDA,
+      config: configPDA,
+      params: paramsPDA,
+      systemProgram
+    };
+    const payout = amountToSell
+      .mul(new anchor.BN(SELL_PRICE))
+      .div(decimalsMultiplier)
+      .mul(new anchor.BN(BPS - TREASURY_SHARE))
+      .div(new anchor.BN(BPS));
+
+    try {
+      await program.methods
+        .userSell(amountToSell, payout.addn(1))
+        .accounts(sellAccounts)
+        .rpc();
+      expect.fail('user_sell should respect the minimum payout');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('SlippageExceeded');
+    }
+
+    try {
+      await program.methods
+        .userSell(new anchor.BN(DAILY_SELL_LIMIT).addn(1), new anchor.BN(0))
+        .accounts(sellAccounts)
+        .rpc();
+      expect.fail('user_sell should respect the daily buyback limit');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('DailySellLimitExceeded');
+    }
+
     const storageTokenBalanceBefore =
       await anchorProvider.connection.getTokenAccountBalance(
         storageTokenAddress
@@ -495,20 +2356,23 @@ describe('Membrane', () => {
       await anchorProvider.connection.getTokenAccountBalance(
         userTokenAccount.address
       );
+    const treasuryLamportsBefore = await anchorProvider.connection.getBalance(
+      treasuryPDA
+    );
 
-    await program.methods
-      .userSell(amountToSell)
-      .accounts({
-        player: user.publicKey,
-        mint: mintAddress,
-        vaultToken: storageTokenAddress,
-        playerToken: userTokenAccount.address,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        authority: storagePDA
-      })
+    const signature = await program.methods
+      .userSell(amountToSell, payout)
+      .accounts(sellAccounts)
       .signers([])
       .rpc();
 
+    const treasuryLamportsAfter = await anchorProvider.connection.getBalance(
+      treasuryPDA
+    );
+    expect(treasuryLamportsBefore - treasuryLamportsAfter).to.equal(
+      payout.toNumber()
+    );
+
     const storageTokenBalanceAfter =
       await anchorProvider.connection.getTokenAccountBalance(
         storageTokenAddress
@@ -521,8 +2385,8 @@ describe('Membrane', () => {
     expect(
       new anchor.BN(storageTokenBalanceAfter.value.amount).eq(
         new anchor.BN(storageTokenBalanceBefore.value.amount).add(
-          // Half of the amount is burned
-          amountToSell.div(new anchor.BN(2))
+          // SELL_BURN of the amount is burned
+          amountToSell.mul(new anchor.BN(BPS - SELL_BURN)).div(new anchor.BN(BPS))
         )
       )
     ).to.be.true;
@@ -531,6 +2395,732 @@ describe('Membrane', () => {
         new anchor.BN(playerTokenBalanceBefore.value.amount).sub(amountToSell)
       )
     ).to.be.true;
+
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('TokensSold');
+    expect(event.data.amount.eq(amountToSell)).to.be.true;
+    expect(event.data.burned.eq(amountToSell.muln(SELL_BURN).divn(BPS))).to.be.true;
+    expect(event.data.payout.eq(payout)).to.be.true;
+  });
+
+  it('Treasurer can withdraw from the treasury', async () => {
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10);
+    const withdraw = (signer: Keypair, lamports: anchor.BN) =>
+      program.methods
+        .withdrawTreasury(lamports)
+        .accounts({
+          treasury: treasuryPDA,
+          to: recipient,
+          storage: signer.publicKey,
+          config: configPDA,
+          systemProgram
+        })
+        .signers([signer])
+        .rpc();
+    const setPause = (paused: number) =>
+      program.methods
+        .setPause(paused)
+        .accounts({
+          config: configPDA,
+          storage: storage.publicKey
+        })
+        .signers([storage])
+        .rpc();
+
+    try {
+      await withdraw(Keypair.generate(), amount);
+      expect.fail('withdraw_treasury should reject a signer other than the treasurer');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MissingRole');
+    }
+
+    // The treasury keeps its rent exemption
+    const treasuryLamports = await anchorProvider.connection.getBalance(
+      treasuryPDA
+    );
+    try {
+      await withdraw(storage, new anchor.BN(treasuryLamports));
+      expect.fail('withdraw_treasury should keep the treasury rent exempt');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('TreasuryDepleted');
+    }
+
+    await setPause(PAUSE.TREASURY);
+    try {
+      await withdraw(storage, amount);
+      expect.fail('withdraw_treasury should stop while the treasury is paused');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('Paused');
+    }
+    await setPause(0);
+
+    const signature = await withdraw(storage, amount);
+    expect(await anchorProvider.connection.getBalance(recipient)).to.equal(
+      amount.toNumber()
+    );
+    const treasuryLamportsAfter = await anchorProvider.connection.getBalance(
+      treasuryPDA
+    );
+    expect(treasuryLamports - treasuryLamportsAfter).to.equal(
+      amount.toNumber()
+    );
+
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('TreasuryWithdrawn');
+    expect(event.data.to.toBase58()).to.equal(recipient.toBase58());
+    expect(event.data.amount.eq(amount)).to.be.true;
+    expect(event.data.balance.toNumber()).to.equal(treasuryLamportsAfter);
+  });
+
+  it('Can provide liquidity and swap in the PLASMA/SOL pool', async () => {
+    const fee = new anchor.BN(30); // 0.3%
+    const vaultShare = new anchor.BN(5_000); // half of the fee to the vault
+    const findPDA = async (seed: string) =>
+      (
+        await anchor.web3.PublicKey.findProgramAddress(
+          [Buffer.from(seed)],
+          program.programId
+        )
+      )[0];
+    const poolPDA = await findPDA(POOL_PDA_SEED);
+    const plasmaReservePDA = await findPDA(POOL_PLASMA_PDA_SEED);
+    const solReservePDA = await findPDA(POOL_SOL_PDA_SEED);
+    const lpMintPDA = await findPDA(POOL_LP_PDA_SEED);
+
+    await program.methods
+      .initializePool(fee, vaultShare)
+      .accounts({
+        pool: poolPDA,
+        plasmaReserve: plasmaReservePDA,
+        solReserve: solReservePDA,
+        lpMint: lpMintPDA,
+        mint: mintAddress,
+        solMint: spl.NATIVE_MINT,
+        storage: storage.publicKey,
+        config: configPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY
+      })
+      .signers([storage])
+      .rpc();
+
+    // The provider is also the trader
+    const provider = Keypair.generate();
+    await getAirdrop(anchorProvider.connection, provider.publicKey, 5);
+    const providerPlasma = await getOrCreateAssociatedTokenAccount(
+      anchorProvider.connection,
+      provider,
+      mintAddress,
+      provider.publicKey
+    );
+    const providerSol = await spl.createWrappedNativeAccount(
+      anchorProvider.connection,
+      provider,
+      provider.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    const providerLp = await getOrCreateAssociatedTokenAccount(
+      anchorProvider.connection,
+      provider,
+      lpMintPDA,
+      provider.publicKey
+    );
+    await program.methods
+      .mintToken(new anchor.BN(adjustSupply(1000, PLASMA_DECIMALS)))
+      .accounts({
+        params: paramsPDA,
+        mint: mintAddress,
+        tokenAccount: providerPlasma.address,
+        authority: storagePDA,
+        storage: storage.publicKey,
+        config: configPDA,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .signers([storage])
+      .rpc();
+
+    const liquidityAccounts = {
+      pool: poolPDA,
+      plasmaReserve: plasmaReservePDA,
+      solReserve: solReservePDA,
+      lpMint: lpMintPDA,
+      provider: provider.publicKey,
+      providerPlasma: providerPlasma.address,
+      providerSol: providerSol,
+      providerLp: providerLp.address,
+      config: configPDA,
+      tokenProgram: TOKEN_PROGRAM_ID
+    };
+    const plasmaDeposit = new anchor.BN(adjustSupply(500, PLASMA_DECIMALS));
+    const solDeposit = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
+
+    await program.methods
+      .addLiquidity(plasmaDeposit, solDeposit, new anchor.BN(0))
+      .accounts(liquidityAccounts)
+      .signers([provider])
+      .rpc();
+
+    // sqrt(500e9 * 1e9) minus the locked minimum liquidity
+    const lpBalance = new anchor.BN(
+      (await getAccount(anchorProvider.connection, providerLp.address)).amount
+        .toString()
+    );
+    expect(lpBalance.eq(isqrt(plasmaDeposit.mul(solDeposit)).subn(1_000))).to.be
+      .true;
+
+    // PLASMA to SOL with the fee taken from the input
+    const amountIn = new anchor.BN(adjustSupply(10, PLASMA_DECIMALS));
+    const feeAmount = amountIn.mul(fee).divn(10_000);
+    const netIn = amountIn.sub(feeAmount);
+    const expectedOut = solDeposit.mul(netIn).div(plasmaDeposit.add(netIn));
+    const feeToVault = feeAmount.mul(vaultShare).divn(10_000);
+    const swapAccounts = {
+      pool: poolPDA,
+      plasmaReserve: plasmaReservePDA,
+      solReserve: solReservePDA,
+      mint: mintAddress,
+      vaultToken: storageTokenAddress,
+      trader: provider.publicKey,
+      traderPlasma: providerPlasma.address,
+      traderSol: providerSol,
+      config: configPDA,
+      tokenProgram: TOKEN_PROGRAM_ID
+    };
+
+    try {
+      await program.methods
+        .swap({ plasmaToSol: {} }, amountIn, expectedOut.addn(1))
+        .accounts(swapAccounts)
+        .signers([provider])
+        .rpc();
+      expect.fail('swap should respect the minimum output');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('SlippageExceeded');
+    }
+
+    // spl-token returns bigints, compare them as BN
+    const toBN = (value: bigint) => new anchor.BN(value.toString());
+    const balances = async () => ({
+      vault: toBN(
+        (await getAccount(anchorProvider.connection, storageTokenAddress))
+          .amount
+      ),
+      supply: toBN((await getMint(anchorProvider.connection, mintAddress)).supply),
+      sol: toBN((await getAccount(anchorProvider.connection, providerSol)).amount)
+    });
+    const before = await balances();
+
+    const signature = await program.methods
+      .swap({ plasmaToSol: {} }, amountIn, expectedOut)
+      .accounts(swapAccounts)
+      .signers([provider])
+      .rpc();
+
+    const after = await balances();
+
+    expect(after.sol.sub(before.sol).eq(expectedOut)).to.be.true;
+    expect(after.vault.sub(before.vault).eq(feeToVault)).to.be.true;
+    expect(before.supply.sub(after.supply).eq(feeAmount.sub(feeToVault))).to.be
+      .true;
+
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('Swapped');
+    expect(event.data.amountOut.eq(expectedOut)).to.be.true;
+
+    // The provider takes its whole share back
+    await program.methods
+      .removeLiquidity(lpBalance, new anchor.BN(0), new anchor.BN(0))
+      .accounts(liquidityAccounts)
+      .signers([provider])
+      .rpc();
+
+    const lpAfter = await getAccount(
+      anchorProvider.connection,
+      providerLp.address
+    );
+    expect(lpAfter.amount.toString()).to.equal('0');
+
+    // The locked liquidity keeps its share, the next deposit pays the pool price for it
+    const plasmaReserve = toBN(
+      (await getAccount(anchorProvider.connection, plasmaReservePDA)).amount
+    );
+    const solReserve = toBN(
+      (await getAccount(anchorProvider.connection, solReservePDA)).amount
+    );
+    expect(plasmaReserve.gtn(0) && solReserve.gtn(0)).to.be.true;
+    const relockedLp = anchor.BN.min(
+      plasmaDeposit.muln(1_000).div(plasmaReserve),
+      solDeposit.muln(1_000).div(solReserve)
+    );
+    await program.methods
+      .addLiquidity(plasmaDeposit, solDeposit, relockedLp)
+      .accounts(liquidityAccounts)
+      .signers([provider])
+      .rpc();
+    const relocked = await getAccount(
+      anchorProvider.connection,
+      providerLp.address
+    );
+    expect(toBN(relocked.amount).eq(relockedLp)).to.be.true;
+
+    // The fee and its split only change through the timelock
+    const proposeAccounts = {
+      proposal: proposalPDA,
+      storage: storage.publicKey,
+      config: configPDA,
+      params: paramsPDA,
+      systemProgram
+    };
+    try {
+      await program.methods
+        .proposePool(new anchor.BN(BPS), vaultShare)
+        .accounts(proposeAccounts)
+        .signers([storage])
+        .rpc();
+      expect.fail('propose_pool should reject a fee out of range');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidPoolParams');
+    }
+
+    await program.methods
+      .proposePool(fee.muln(2), new anchor.BN(BPS))
+      .accounts(proposeAccounts)
+      .signers([storage])
+      .rpc();
+    const poolProposal = await program.account.proposal.fetch(proposalPDA);
+    expect(poolProposal.change.pool.fee.eq(fee.muln(2))).to.be.true;
+    expect(poolProposal.change.pool.vaultShare.eqn(BPS)).to.be.true;
+
+    try {
+      await program.methods
+        .executeChange()
+        .accounts({
+          proposal: proposalPDA,
+          proposer: storage.publicKey,
+          config: configPDA,
+          params: paramsPDA,
+          nftMultiplier: nftMultiplierPDA
+        })
+        .remainingAccounts([
+          { pubkey: poolPDA, isWritable: true, isSigner: false }
+        ])
+        .rpc();
+      expect.fail('execute_change should wait for the timelock');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('TimelockNotExpired');
+    }
+    const poolAccount = await program.account.pool.fetch(poolPDA);
+    expect(poolAccount.fee.eq(fee)).to.be.true;
+
+    await program.methods
+      .cancelChange()
+      .accounts({
+        proposal: proposalPDA,
+        storage: storage.publicKey,
+        config: configPDA
+      })
+      .signers([storage])
+      .rpc();
+  });
+
+  it('Can pause and resume the minting', async () => {
+    const setPause = (admin: Keypair, paused: number) =>
+      program.methods
+        .setPause(paused)
+        .accounts({
+          config: configPDA,
+          storage: admin.publicKey
+        })
+        .signers([admin])
+        .rpc();
+    const mint = () =>
+      program.methods
+        .mintToken(new anchor.BN(1))
+        .accounts({
+          params: paramsPDA,
+          mint: mintAddress,
+          tokenAccount: storageTokenAddress,
+          authority: storagePDA,
+          storage: storage.publicKey,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([storage])
+        .rpc();
+
+    try {
+      await setPause(Keypair.generate(), PAUSE.MINTING);
+      expect.fail('set_pause should reject a signer other than the admin');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('Unauthorized');
+    }
+
+    try {
+      await setPause(storage, 1 << 7);
+      expect.fail('set_pause should reject an unknown instruction group');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidPauseFlags');
+    }
+
+    const signature = await setPause(storage, PAUSE.MINTING | PAUSE.SELLS);
+    const [event] = await getEvents(program, signature);
+    expect(event.name).to.equal('PauseUpdated');
+    expect(event.data.paused).to.equal(PAUSE.MINTING | PAUSE.SELLS);
+
+    try {
+      await mint();
+      expect.fail('mint_token should stop while minting is paused');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('Paused');
+    }
+
+    await setPause(storage, 0);
+    await mint();
+  });
+
+  it('Can freeze and thaw the vault', async () => {
+    const accounts = {
+      mint: mintAddress,
+      storage: storage.publicKey,
+      storageTokenAccount: storageTokenAddress,
+      authority: storagePDA,
+      config: configPDA,
+      tokenProgram: TOKEN_PROGRAM_ID
+    };
+    const tokenAccountBefore = await getAccount(
+      anchorProvider.connection,
+      storageTokenAddress
+    );
+
+    expect(tokenAccountBefore.isFrozen).to.be.false;
+
+    // The PDA holds the freeze authority and signs for the treasurer
+    await program.methods
+      .freezeStorage()
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+
+    let tokenAccountAfter = await getAccount(
+      anchorProvider.connection,
+      storageTokenAddress
+    );
+    expect(tokenAccountAfter.isFrozen).to.be.true;
+
+    await program.methods
+      .thawStorage()
+      .accounts(accounts)
+      .signers([storage])
+      .rpc();
+
+    tokenAccountAfter = await getAccount(
+      anchorProvider.connection,
+      storageTokenAddress
+    );
+    expect(tokenAccountAfter.isFrozen).to.be.false;
+  });
+
+  it('Can require the multisig for the treasury', async () => {
+    const [ownerB, ownerC] = multisigOwners;
+    const largeMint = new anchor.BN(adjustSupply(10, PLASMA_DECIMALS));
+    [multisigPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from(MULTISIG_PDA_SEED)],
+      program.programId
+    );
+    const [transactionPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from(MULTISIG_TX_PDA_SEED), Buffer.from('0')],
+      program.programId
+    );
+
+    const initSignature = await program.methods
+      .initializeMultisig(
+        [storage.publicKey, ownerB.publicKey, ownerC.publicKey],
+        2,
+        largeMint
+      )
+      .accounts({
+        multisig: multisigPDA,
+        storage: storage.publicKey,
+        config: configPDA,
+        systemProgram
+      })
+      .signers([storage])
+      .rpc();
+
+    const [initEvent] = await getEvents(program, initSignature);
+    expect(initEvent.name).to.equal('MultisigInitialized');
+    expect(initEvent.data.threshold).to.equal(2);
+
+    const configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.multisigEnabled).to.be.true;
+    expect(configAccount.largeMint.eq(largeMint)).to.be.true;
+
+    // Mints above the large mint amount need the multisig
+    const amount = largeMint.mul(new anchor.BN(2));
+    try {
+      await program.methods
+        .mintToken(amount)
+        .accounts({
+          params: paramsPDA,
+          mint: mintAddress,
+          tokenAccount: storageTokenAddress,
+          authority: storagePDA,
+          storage: storage.publicKey,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('mint_token should require the multisig for a large mint');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MultisigRequired');
+    }
+
+    // So does the admin rotation
+    try {
+      await program.methods
+        .proposeAdmin(ownerB.publicKey)
+        .accounts({
+          config: configPDA,
+          storage: storage.publicKey,
+          authority: storagePDA,
+          mint: mintAddress,
+          vaultToken: storageTokenAddress
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('propose_admin should require the multisig');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MultisigRequired');
+    }
+
+    // Small mints count towards the daily total, which is already past the large mint amount
+    try {
+      await program.methods
+        .mintToken(new anchor.BN(1))
+        .accounts({
+          params: paramsPDA,
+          mint: mintAddress,
+          tokenAccount: storageTokenAddress,
+          authority: storagePDA,
+          storage: storage.publicKey,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('mint_token should require the multisig past the daily large mint amount');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MultisigRequired');
+    }
+
+    // And so do the roles, the admin cannot make itself treasurer
+    try {
+      await program.methods
+        .setRoles(storage.publicKey, storage.publicKey, storage.publicKey)
+        .accounts({
+          config: configPDA,
+          storage: storage.publicKey
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('set_roles should require the multisig');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MultisigRequired');
+    }
+
+    await program.methods
+      .proposeTransaction(new anchor.BN(0), {
+        mintToken: { to: storageTokenAddress, amount }
+      })
+      .accounts({
+        transaction: transactionPDA,
+        multisig: multisigPDA,
+        owner: storage.publicKey,
+        systemProgram
+      })
+      .signers([storage])
+      .rpc();
+
+    const executeAccounts = {
+      transaction: transactionPDA,
+      proposer: storage.publicKey,
+      multisig: multisigPDA,
+      config: configPDA,
+      params: paramsPDA,
+      authority: storagePDA,
+      mint: mintAddress,
+      vaultToken: storageTokenAddress,
+      treasury: treasuryPDA,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram
+    };
+    const mintTarget = [
+      { pubkey: storageTokenAddress, isWritable: true, isSigner: false }
+    ];
+
+    // The proposal is only one approval out of two
+    try {
+      await program.methods
+        .executeTransaction(new anchor.BN(0))
+        .accounts(executeAccounts)
+        .remainingAccounts(mintTarget)
+        .rpc();
+      expect.fail('execute_transaction should wait for the threshold');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('NotEnoughApprovals');
+    }
+
+    // Only the owners can approve
+    try {
+      await program.methods
+        .approveTransaction(new anchor.BN(0))
+        .accounts({
+          transaction: transactionPDA,
+          multisig: multisigPDA,
+          owner: anchorProvider.wallet.publicKey
+        })
+        .rpc();
+      expect.fail('approve_transaction should reject a non-owner');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('NotMultisigOwner');
+    }
+
+    await program.methods
+      .approveTransaction(new anchor.BN(0))
+      .accounts({
+        transaction: transactionPDA,
+        multisig: multisigPDA,
+        owner: ownerB.publicKey
+      })
+      .signers([ownerB])
+      .rpc();
+
+    const balanceBefore =
+      await anchorProvider.connection.getTokenAccountBalance(
+        storageTokenAddress
+      );
+
+    // The minted account has to be the one named by the action
+    try {
+      await program.methods
+        .executeTransaction(new anchor.BN(0))
+        .accounts(executeAccounts)
+        .remainingAccounts([
+          { pubkey: treasuryTokenAddress, isWritable: true, isSigner: false }
+        ])
+        .rpc();
+      expect.fail('execute_transaction should reject another target');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('InvalidTarget');
+    }
+
+    const signature = await program.methods
+      .executeTransaction(new anchor.BN(0))
+      .accounts(executeAccounts)
+      .remainingAccounts(mintTarget)
+      .rpc();
+
+    const balanceAfter = await anchorProvider.connection.getTokenAccountBalance(
+      storageTokenAddress
+    );
+    expect(
+      new anchor.BN(balanceAfter.value.amount).eq(
+        new anchor.BN(balanceBefore.value.amount).add(amount)
+      )
+    ).to.be.true;
+
+    const events = await getEvents(program, signature);
+    expect(events.map((event) => event.name)).to.deep.equal([
+      'TokensMinted',
+      'TransactionExecuted'
+    ]);
+
+    const transactionAccount =
+      await program.account.multisigTransaction.fetchNullable(transactionPDA);
+    expect(transactionAccount).to.be.null;
+
+    // The treasurer cannot return the authority on its own anymore
+    try {
+      await program.methods
+        .returnAuthority()
+        .accounts({
+          storage: storage.publicKey,
+          mint: mintAddress,
+          storageTokenAccount: storageTokenAddress,
+          pda: storagePDA,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('return_authority should require the multisig');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MultisigRequired');
+    }
+
+    // Nor withdraw from the treasury
+    try {
+      await program.methods
+        .withdrawTreasury(new anchor.BN(1))
+        .accounts({
+          treasury: treasuryPDA,
+          to: storage.publicKey,
+          storage: storage.publicKey,
+          config: configPDA,
+          systemProgram
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('withdraw_treasury should require the multisig');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('MultisigRequired');
+    }
+
+    // Roles are assigned through the multisig, the action only touches the config
+    const [rolesTransactionPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from(MULTISIG_TX_PDA_SEED), Buffer.from('1')],
+      program.programId
+    );
+    await program.methods
+      .proposeTransaction(new anchor.BN(1), {
+        setRoles: {
+          oracle: storage.publicKey,
+          treasurer: storage.publicKey,
+          paramManager: storage.publicKey
+        }
+      })
+      .accounts({
+        transaction: rolesTransactionPDA,
+        multisig: multisigPDA,
+        owner: storage.publicKey,
+        systemProgram
+      })
+      .signers([storage])
+      .rpc();
+    await program.methods
+      .approveTransaction(new anchor.BN(1))
+      .accounts({
+        transaction: rolesTransactionPDA,
+        multisig: multisigPDA,
+        owner: ownerC.publicKey
+      })
+      .signers([ownerC])
+      .rpc();
+
+    const rolesSignature = await program.methods
+      .executeTransaction(new anchor.BN(1))
+      .accounts({ ...executeAccounts, transaction: rolesTransactionPDA })
+      .rpc();
+
+    const rolesEvents = await getEvents(program, rolesSignature);
+    expect(rolesEvents.map((event) => event.name)).to.deep.equal([
+      'RolesUpdated',
+      'TransactionExecuted'
+    ]);
   });
 
   it('Can return the authority back to the storage', async () => {
@@ -544,28 +3134,67 @@ describe('Membrane', () => {
     );
     const storagePDABalanceBefore =
       await anchorProvider.connection.getAccountInfo(storagePDA);
-    const storageBalanceBefore = await anchorProvider.connection.getAccountInfo(
-      storage.publicKey
-    );
     const storagePDALamportsBefore = storagePDABalanceBefore?.lamports || 0;
-    const storageLamportsBefore = storageBalanceBefore?.lamports || 0;
 
     expect(mintInfoBefore.mintAuthority.toBase58()).to.equal(
       storagePDA.toBase58()
     );
     expect(tokenAccountBefore.owner.toBase58()).to.equal(storagePDA.toBase58());
 
+    // Returned through the multisig, storage proposes and a co-owner approves
+    const [transactionPDA] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from(MULTISIG_TX_PDA_SEED), Buffer.from('2')],
+      program.programId
+    );
+
     await program.methods
-      .returnAuthority()
+      .proposeTransaction(new anchor.BN(2), {
+        returnAuthority: { to: storage.publicKey }
+      })
       .accounts({
-        storage: storage.publicKey,
+        transaction: transactionPDA,
+        multisig: multisigPDA,
+        owner: storage.publicKey,
+        systemProgram
+      })
+      .signers([storage])
+      .rpc();
+
+    await program.methods
+      .approveTransaction(new anchor.BN(2))
+      .accounts({
+        transaction: transactionPDA,
+        multisig: multisigPDA,
+        owner: multisigOwners[0].publicKey
+      })
+      .signers([multisigOwners[0]])
+      .rpc();
+
+    const storageLamportsProposed =
+      (await anchorProvider.connection.getAccountInfo(storage.publicKey))
+        ?.lamports || 0;
+    const transactionRent =
+      (await anchorProvider.connection.getAccountInfo(transactionPDA))
+        ?.lamports || 0;
+
+    await program.methods
+      .executeTransaction(new anchor.BN(2))
+      .accounts({
+        transaction: transactionPDA,
+        proposer: storage.publicKey,
+        multisig: multisigPDA,
+        config: configPDA,
+        params: paramsPDA,
+        authority: storagePDA,
         mint: mintAddress,
-        storageTokenAccount: storageTokenAddress,
-        pda: storagePDA,
+        vaultToken: storageTokenAddress,
+        treasury: treasuryPDA,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram
       })
-      .signers([])
+      .remainingAccounts([
+        { pubkey: storage.publicKey, isWritable: true, isSigner: false }
+      ])
       .rpc();
 
     const mintInfoAfter = await getMint(anchorProvider.connection, mintAddress);
@@ -589,34 +3218,190 @@ describe('Membrane', () => {
     );
     expect(storagePDALamportsAfter).to.equal(0);
     expect(storageLamportsAfter).to.equal(
-      storageLamportsBefore + storagePDALamportsBefore
+      storageLamportsProposed + storagePDALamportsBefore + transactionRent
     );
   });
 
-  it('Can freeze the authority for mint', async () => {
-    const tokenAccountBefore = await getAccount(
-      anchorProvider.connection,
-      storageTokenAddress
-    );
+  it('Cannot freeze the vault once the authority is returned', async () => {
+    try {
+      await program.methods
+        .freezeStorage()
+        .accounts({
+          mint: mintAddress,
+          storage: storage.publicKey,
+          storageTokenAccount: storageTokenAddress,
+          authority: storagePDA,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([storage])
+        .rpc();
+      expect.fail('freeze_storage should reject a freeze authority other than the PDA');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('AuthorityNotTransferred');
+    }
+  });
 
-    expect(tokenAccountBefore.isFrozen).to.be.false;
+  it('Can manage the roles and the owners through the multisig once the authority is returned', async () => {
+    const [ownerB, ownerC] = multisigOwners;
+    const newOwner = Keypair.generate();
+    const findTransaction = async (nonce: number) =>
+      (
+        await anchor.web3.PublicKey.findProgramAddress(
+          [Buffer.from(MULTISIG_TX_PDA_SEED), Buffer.from(nonce.toString())],
+          program.programId
+        )
+      )[0];
+    const propose = async (nonce: number, action: object) =>
+      program.methods
+        .proposeTransaction(new anchor.BN(nonce), action)
+        .accounts({
+          transaction: await findTransaction(nonce),
+          multisig: multisigPDA,
+          owner: storage.publicKey,
+          systemProgram
+        })
+        .signers([storage])
+        .rpc();
+    const approve = async (nonce: number, owner: Keypair) =>
+      program.methods
+        .approveTransaction(new anchor.BN(nonce))
+        .accounts({
+          transaction: await findTransaction(nonce),
+          multisig: multisigPDA,
+          owner: owner.publicKey
+        })
+        .signers([owner])
+        .rpc();
+    const execute = async (nonce: number, target: PublicKey[] = []) =>
+      program.methods
+        .executeTransaction(new anchor.BN(nonce))
+        .accounts({
+          transaction: await findTransaction(nonce),
+          proposer: storage.publicKey,
+          multisig: multisigPDA,
+          config: configPDA,
+          params: paramsPDA,
+          authority: storagePDA,
+          mint: mintAddress,
+          vaultToken: storageTokenAddress,
+          treasury: treasuryPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram
+        })
+        .remainingAccounts(
+          target.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc();
+
+    // Config actions do not need the authorities
+    await propose(3, {
+      setRoles: {
+        oracle: ownerB.publicKey,
+        treasurer: storage.publicKey,
+        paramManager: storage.publicKey
+      }
+    });
+    await approve(3, ownerB);
+    const rolesSignature = await execute(3);
+    expect(
+      (await getEvents(program, rolesSignature)).map((event) => event.name)
+    ).to.deep.equal(['RolesUpdated', 'TransactionExecuted']);
+    const configAccount = await program.account.config.fetch(configPDA);
+    expect(configAccount.oracle.toBase58()).to.equal(
+      ownerB.publicKey.toBase58()
+    );
 
-    await program.methods
-      .freezeStorage()
-      .accounts({
-        mint: mintAddress,
-        storage: storage.publicKey,
-        storageTokenAccount: storageTokenAddress,
-        tokenProgram: TOKEN_PROGRAM_ID
-      })
-      .signers([storage])
-      .rpc();
+    // Token actions do
+    await propose(4, {
+      mintToken: { to: storageTokenAddress, amount: new anchor.BN(1) }
+    });
+    await approve(4, ownerB);
+    try {
+      await program.methods
+        .executeTransaction(new anchor.BN(4))
+        .accounts({
+          transaction: await findTransaction(4),
+          proposer: storage.publicKey,
+          multisig: multisigPDA,
+          config: configPDA,
+          params: paramsPDA,
+          authority: storagePDA,
+          mint: mintAddress,
+          vaultToken: storageTokenAddress,
+          treasury: treasuryPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram
+        })
+        .remainingAccounts([
+          { pubkey: storageTokenAddress, isWritable: true, isSigner: false }
+        ])
+        .rpc();
+      expect.fail('execute_transaction should not mint without the authority');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('AuthorityNotTransferred');
+    }
 
-    const tokenAccountAfter = await getAccount(
-      anchorProvider.connection,
-      storageTokenAddress
+    // A new owner joins and the threshold goes up
+    await propose(5, {
+      addOwner: { owner: newOwner.publicKey, threshold: 3 }
+    });
+    await approve(5, ownerC);
+    await propose(6, { changeThreshold: { threshold: 2 } }); // proposed to the old owners
+    const addSignature = await execute(5);
+
+    const [updatedEvent] = await getEvents(program, addSignature);
+    expect(updatedEvent.name).to.equal('MultisigUpdated');
+    expect(updatedEvent.data.threshold).to.equal(3);
+    let multisigAccount = await program.account.multisig.fetch(multisigPDA);
+    expect(multisigAccount.owners.map((owner) => owner.toBase58())).to.deep.equal(
+      [storage, ownerB, ownerC, newOwner].map((owner) =>
+        owner.publicKey.toBase58()
+      )
     );
+    expect(multisigAccount.threshold).to.equal(3);
+
+    // Approvals of the earlier owners do not carry over
+    try {
+      await approve(6, ownerB);
+      expect.fail('approve_transaction should reject a stale transaction');
+    } catch (err) {
+      expect(err.error?.errorCode?.code).to.equal('StaleTransaction');
+    }
+    for (const nonce of [4, 6]) {
+      await program.methods
+        .cancelTransaction(new anchor.BN(nonce))
+        .accounts({
+          transaction: await findTransaction(nonce),
+          proposer: storage.publicKey
+        })
+        .signers([storage])
+        .rpc();
+    }
 
-    expect(tokenAccountAfter.isFrozen).to.be.true;
+    // The owner leaves again and the threshold goes back down
+    await propose(7, {
+      removeOwner: { owner: newOwner.publicKey, threshold: 2 }
+    });
+    await approve(7, ownerB);
+    await approve(7, newOwner);
+    await execute(7);
+
+    multisigAccount = await program.account.multisig.fetch(multisigPDA);
+    expect(multisigAccount.owners).to.have.lengthOf(3);
+    expect(multisigAccount.threshold).to.equal(2);
+
+    // The treasury stays reachable, it does not depend on the authorities
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10);
+    await propose(8, { withdrawTreasury: { to: recipient, amount } });
+    await approve(8, ownerC);
+    const withdrawSignature = await execute(8, [recipient]);
+    expect(
+      (await getEvents(program, withdrawSignature)).map((event) => event.name)
+    ).to.deep.equal(['TreasuryWithdrawn', 'TransactionExecuted']);
+    expect(await anchorProvider.connection.getBalance(recipient)).to.equal(
+      amount.toNumber()
+    );
   });
 });
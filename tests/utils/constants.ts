@@ -3,11 +3,17 @@ export const PLASMA_DECIMALS = 9;
 export const PLASMA_INITIAL_SUPPLY = 812_500_000;
 export const MEMBRANE_DECIMALS = 9;
 export const MEMBRANE_INITIAL_SUPPLY = 812_500_000;
+export const PLASMA_MAX_SUPPLY = 1_000_000_000;
+export const PLASMA_DAILY_MINT_CAP = 1_000_000;
+export const PLASMA_EMISSION_PER_DAY = 10_000; // vault top ups released every day
 
 // Mocks
 export const DECIMAL_PLACES = 0;
 export const NFT_GRADE_MULTIPLIERS = {
-  COMMON: 16_800_000_000
+  COMMON: 16_800_000_000,
+  RARE: 14_933_333_333,
+  EPIC: 13_440_000_000,
+  LEGENDARY: 10_080_000_000
 };
 
 // Copy from programs/membrane/src/constants.rs
@@ -18,17 +24,39 @@ export const PUBKEY_MAX: number = 32;
 export const DISCRIMINATOR: number = 8;
 
 // Consts for reward calculation
+export const NFT_QUALITIES: number = 4;
 export const MAX_SIZE_REWARD: number =
   DISCRIMINATOR +     // discriminator
-  FLOAT_MAX +         // victory
-  FLOAT_MAX +         // top_five
-  FLOAT_MAX +         // top_ten
-  FLOAT_MAX +         // kills
-  FLOAT_MAX;          // days
+  NFT_QUALITIES * (
+    FLOAT_MAX +       // victory
+    FLOAT_MAX +       // top_five
+    FLOAT_MAX +       // top_ten
+    FLOAT_MAX         // kills
+  ) +
+  FLOAT_MAX +         // days
+  1;                  // bump
 
+// Initial economy parameters of the params account
 export const NFT_PRICE: number = 150_000_000_000;
-export const EULER_NUMBER: number = 2.718_281_828;
 export const FEE_LAMPORTS: number = 100_000_000;
+// Buyback of user_sell
+export const BPS: number = 10_000;
+export const SELL_PRICE: number = 1_000_000; // lamports per PLASMA
+export const SELL_BURN: number = 5_000; // half of the sold PLASMA is burned
+export const TREASURY_SHARE: number = 1_000; // 10% of the price
+export const DAILY_SELL_LIMIT: string = '100000000000000'; // 100k PLASMA
+// Placement shares of the reward in REWARD_SHARE_BASE parts
+export const REWARD_SHARE_BASE: number = 300;
+export const VICTORY_SHARE: number = 300; // 1
+export const TOP_FIVE_SHARE: number = 75; // 1/4
+export const TOP_TEN_SHARE: number = 30; // 1/10
+export const KILL_SHARE: number = 14; // 7/150
+
+// Fixed-point reward curve, scaled by 1e18 (strings, they overflow a number)
+export const PRECISION: number = 1_000_000_000;
+export const CURVE_OFFSET: string = '10000000000000000000';
+export const CURVE_SLOPE: string = '500000000000000000';
+export const CURVE_EXPONENT: string = '400200000000000000';
 
 // UNIX values
 export const START: number = 1654797600;
@@ -42,5 +70,44 @@ export const MAX_PLAYER_SIZE: number =
   FLOAT_MAX +         // claimable
   FLOAT_MAX;          // nft_counter
 
+// Seconds between proposing a parameter change and executing it
+export const TIMELOCK_DELAY: number = 24 * 3600;
+
+// Reward history ring buffer
+export const REWARD_HISTORY_LEN: number = 60;
+
+// Game account
+export const MAX_PLAYERS: number = 100;
+export const GAME_STATUS = {
+  CREATED: 0,
+  RUNNING: 1,
+  ENDED: 2,
+  SETTLED: 3,
+  CANCELLED: 4
+};
+
+// Pause flags of the config account
+export const PAUSE = {
+  REWARDS: 1,
+  CLAIMS: 1 << 1,
+  SELLS: 1 << 2,
+  MINTING: 1 << 3,
+  POOL: 1 << 4,
+  TREASURY: 1 << 5
+};
+
 // PDA SEEDS
 export const VAULT_PDA_SEED: string = 'vault';
+export const CONFIG_PDA_SEED: string = 'config';
+export const REWARD_PDA_SEED: string = 'reward';
+export const MULTIPLIER_PDA_SEED: string = 'multiplier';
+export const REWARD_HISTORY_PDA_SEED: string = 'history';
+export const PARAMS_PDA_SEED: string = 'params';
+export const PROPOSAL_PDA_SEED: string = 'proposal';
+export const TREASURY_PDA_SEED: string = 'treasury';
+export const POOL_PDA_SEED: string = 'pool';
+export const POOL_PLASMA_PDA_SEED: string = 'pool_plasma';
+export const POOL_SOL_PDA_SEED: string = 'pool_sol';
+export const POOL_LP_PDA_SEED: string = 'pool_lp';
+export const MULTISIG_PDA_SEED: string = 'multisig';
+export const MULTISIG_TX_PDA_SEED: string = 'multisig_tx';
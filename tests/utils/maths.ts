@@ -0,0 +1,146 @@
+import { BN } from '@project-serum/anchor';
+import {
+  CURVE_EXPONENT,
+  CURVE_OFFSET,
+  CURVE_SLOPE,
+  KILL_SHARE,
+  NFT_PRICE,
+  PRECISION,
+  REWARD_SHARE_BASE,
+  TOP_FIVE_SHARE,
+  TOP_TEN_SHARE,
+  VICTORY_SHARE
+} from './constants';
+
+// Mirror of programs/membrane/src/maths/fixed_point.rs
+// Values are scaled by SCALE (1e18), every division truncates toward zero
+// exactly like the program does, so the results are bit-exact.
+
+export const SCALE = new BN('1000000000000000000');
+export const LN_2 = new BN('693147180559945309');
+
+const ZERO = new BN(0);
+const ONE = new BN(1);
+const TWO = new BN(2);
+
+export const ln = (x: BN): BN => {
+  if (x.lte(ZERO)) {
+    throw new Error('ln is undefined for x <= 0');
+  }
+
+  // Reduce x to m * 2^k with m in [1, 2)
+  let k = 0;
+  let m = x.clone();
+  while (m.gte(SCALE.mul(TWO))) {
+    m = m.shrn(1);
+    k += 1;
+  }
+  while (m.lt(SCALE)) {
+    m = m.shln(1);
+    k -= 1;
+  }
+
+  // ln(m) = 2 * (s + s^3/3 + s^5/5 + ...) with s = (m - 1)/(m + 1)
+  const s = m.sub(SCALE).mul(SCALE).div(m.add(SCALE));
+  const sSquared = s.mul(s).div(SCALE);
+  let power = s;
+  let sum = new BN(0);
+  let n = new BN(1);
+  while (power.gt(ZERO)) {
+    sum = sum.add(power.div(n));
+    power = power.mul(sSquared).div(SCALE);
+    n = n.add(TWO);
+  }
+
+  return new BN(k).mul(LN_2).add(sum.mul(TWO));
+};
+
+// Returns null when the result does not fit into u128
+export const exp = (y: BN): BN | null => {
+  if (y.lt(ZERO)) {
+    const inverse = exp(y.neg());
+    return inverse === null ? new BN(0) : SCALE.mul(SCALE).div(inverse);
+  }
+
+  // Reduce y to k * ln(2) + r with r in [0, ln(2))
+  const k = y.div(LN_2);
+  const r = y.sub(k.mul(LN_2));
+  if (k.gtn(64)) {
+    return null;
+  }
+
+  // e^r = 1 + r + r^2/2! + r^3/3! + ...
+  let term = SCALE.clone();
+  let sum = SCALE.clone();
+  let n = new BN(1);
+  while (term.gt(ZERO)) {
+    term = term.mul(r).div(n.mul(SCALE));
+    sum = sum.add(term);
+    n = n.add(ONE);
+  }
+
+  return sum.shln(k.toNumber());
+};
+
+export const pow = (x: BN, p: BN): BN | null => {
+  if (x.isZero()) {
+    return new BN(0);
+  }
+  return exp(ln(x).mul(p).div(SCALE));
+};
+
+export const sigmoid = (z: BN): BN => {
+  const value = exp(z.neg());
+  return value === null ? new BN(0) : SCALE.mul(SCALE).div(SCALE.add(value));
+};
+
+// Value of the reward curve on the given day scaled by PRECISION
+export const rewardMultiplier = (days: number): BN => {
+  const x = new BN(Math.max(days, 0)).mul(SCALE);
+  const decay = pow(x, new BN(CURVE_EXPONENT))
+    .mul(new BN(CURVE_SLOPE))
+    .div(SCALE);
+  const z = new BN(CURVE_OFFSET).sub(decay);
+  return sigmoid(z).div(SCALE.div(new BN(PRECISION)));
+};
+
+export type RewardTable = {
+  victory: BN;
+  topFive: BN;
+  topTen: BN;
+  kill: BN;
+};
+
+// Mirror of Reward::calculate_reward
+export const calculateRewardTable = (
+  days: number,
+  nftGradeMultiplier: BN
+): RewardTable => {
+  const multiplier = rewardMultiplier(days);
+  const share = (parts: number): BN =>
+    new BN(NFT_PRICE)
+      .muln(parts)
+      .mul(multiplier)
+      .div(nftGradeMultiplier.muln(REWARD_SHARE_BASE));
+
+  return {
+    victory: share(VICTORY_SHARE),
+    topFive: share(TOP_FIVE_SHARE),
+    topTen: share(TOP_TEN_SHARE),
+    kill: share(KILL_SHARE)
+  };
+};
+
+// Mirror of pool_state::isqrt, sizes the first deposit of the pool
+export const isqrt = (value: BN): BN => {
+  if (value.lt(TWO)) {
+    return value;
+  }
+  let x = value.div(TWO).add(ONE);
+  let y = x.add(value.div(x)).div(TWO);
+  while (y.lt(x)) {
+    x = y;
+    y = x.add(value.div(x)).div(TWO);
+  }
+  return x;
+};
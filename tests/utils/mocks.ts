@@ -1,9 +1,6 @@
 import { BN } from '@project-serum/anchor';
 import Decimal from 'decimal.js';
 import {
-  DECIMAL_PLACES,
-  EULER_NUMBER,
-  NFT_PRICE,
   PLASMA_DECIMALS,
   PLASMA_INITIAL_SUPPLY,
   SEC_IN_DAY,
@@ -11,6 +8,7 @@ import {
 } from './constants';
 import { Connection, Keypair, PublicKey } from '@solana/web3.js';
 import { adjustSupply, createToken, mintToken } from './web3';
+import { calculateRewardTable } from './maths';
 
 export type Stat = {
   id: PublicKey;
@@ -59,55 +57,11 @@ export type RewardParams = {
 };
 
 export const calculateInitialRewardParams = (
-  nftGradeMultiplier: number,
-  decimals: number
+  nftGradeMultiplier: number
 ): RewardParams => {
-  // Use Decimal for operations with numbers
-  const dateNow = new Decimal(Date.now());
-  const start = new Decimal(START);
-  const secInDay = new Decimal(SEC_IN_DAY);
-  const eulerNumber = new Decimal(EULER_NUMBER);
-  const a = new Decimal(10);
-  const b = new Decimal(0.5);
-  const c = new Decimal(0.4);
-  const d = new Decimal(1.0005);
-  const currentUsers = new Decimal(1); // Value is not final
-  const initialUsers = new Decimal(1); // Value is not final
-  const nftPrice = new Decimal(NFT_PRICE);
-  const victoryParam = new Decimal(1);
-  const topFiveParam = new Decimal(4);
-  const topTenParam = new Decimal(10);
-  const killParam = new Decimal(150);
-
-  const unixNow = dateNow.divToInt(new Decimal(1000)); // Should be an integer
-  const x = unixNow.sub(start).divToInt(secInDay); // Should be an integer
-  const xExp = c.mul(d.pow(currentUsers.div(initialUsers)));
-  const denominatorExp = a.sub(b.mul(x.pow(xExp)));
-  const denominator = eulerNumber.pow(denominatorExp).add(new Decimal(1));
-  const multiplier = new Decimal(1)
-    .sub(new Decimal(1).div(denominator))
-    .mul(new Decimal(10).pow(decimals))
-    .toDecimalPlaces(DECIMAL_PLACES, Decimal.ROUND_DOWN);
-
-  const victory = nftPrice.div(victoryParam.mul(nftGradeMultiplier)).mul(multiplier);
-  const topFive = nftPrice.div(topFiveParam.mul(nftGradeMultiplier)).mul(multiplier);
-  const topTen = nftPrice.div(topTenParam.mul(nftGradeMultiplier)).mul(multiplier);
-  const kill = nftPrice.mul(7).div(killParam.mul(nftGradeMultiplier)).mul(multiplier);
-
-  return {
-    victory: new BN(
-      victory.toDecimalPlaces(DECIMAL_PLACES, Decimal.ROUND_DOWN).toNumber()
-    ),
-    topFive: new BN(
-      topFive.toDecimalPlaces(DECIMAL_PLACES, Decimal.ROUND_DOWN).toNumber()
-    ),
-    topTen: new BN(
-      topTen.toDecimalPlaces(DECIMAL_PLACES, Decimal.ROUND_DOWN).toNumber()
-    ),
-    kill: new BN(
-      kill.toDecimalPlaces(DECIMAL_PLACES, Decimal.ROUND_DOWN).toNumber()
-    )
-  };
+  const unixNow = Math.floor(Date.now() / 1000);
+  const days = Math.floor((unixNow - START) / SEC_IN_DAY);
+  return calculateRewardTable(days, new BN(nftGradeMultiplier));
 };
 
 export const getRandomInt = (min: number, max: number): number => {
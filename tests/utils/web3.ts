@@ -16,6 +16,48 @@ import {
   ASSOCIATED_TOKEN_PROGRAM_ID
 } from '@solana/spl-token';
 import Decimal from 'decimal.js';
+import { Event, EventParser, Program } from '@project-serum/anchor';
+
+// Compute units consumed by the program, read from the transaction logs
+export const getComputeUnits = async (
+  connection: Connection,
+  programId: PublicKey,
+  signature: TransactionSignature
+): Promise<number> => {
+  await connection.confirmTransaction(signature, 'confirmed');
+  const transaction = await connection.getTransaction(signature, {
+    commitment: 'confirmed'
+  });
+  const pattern = new RegExp(
+    `^Program ${programId.toBase58()} consumed (\\d+) of \\d+ compute units$`
+  );
+  for (const log of transaction?.meta?.logMessages || []) {
+    const match = log.match(pattern);
+    if (match) {
+      return Number(match[1]);
+    }
+  }
+  throw new Error(`No compute units logged for ${signature}`);
+};
+
+// Anchor events emitted by the program, read from the transaction logs
+export const getEvents = async (
+  program: Program<any>,
+  signature: TransactionSignature
+): Promise<Event[]> => {
+  const connection = program.provider.connection;
+  await connection.confirmTransaction(signature, 'confirmed');
+  const transaction = await connection.getTransaction(signature, {
+    commitment: 'confirmed'
+  });
+
+  const events: Event[] = [];
+  const parser = new EventParser(program.programId, program.coder);
+  parser.parseLogs(transaction?.meta?.logMessages || [], (event) =>
+    events.push(event)
+  );
+  return events;
+};
 
 export const getAirdrop = async (
   connection: Connection,